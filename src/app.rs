@@ -0,0 +1,642 @@
+use crate::collector;
+use crate::details;
+use crate::disks::{DiskList, DiskView};
+use crate::filter::ProcessFilter;
+use crate::history::{History, DEFAULT_HISTORY};
+use crate::keys::{Action, KeyBindings};
+use crate::layout::Panel;
+use crate::processes::{ProcessInfo, SortColumn};
+use crate::signals::{self, SignalTarget, SIGNALS};
+use crate::theme::Theme;
+use crate::users::UserCache;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessesToUpdate, System};
+use tui::widgets::TableState;
+
+// dialog drawn on top of the main view, it receives all key presses while open
+pub enum Popup {
+    SignalPicker {
+        target: SignalTarget,
+        selected: usize,
+    },
+    ConfirmSignal {
+        target: SignalTarget,
+        selected: usize,
+    },
+    Details {
+        pid: Pid,
+        scroll: u16,
+    },
+}
+
+// value being typed into the prompt in the status line
+pub enum PromptKind {
+    Threshold,
+    Filter,
+}
+
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: String,
+}
+
+// message shown in the status line after an action
+pub enum Status {
+    Info(String),
+    Error(String),
+}
+
+pub struct App {
+    pub running: bool,
+    pub popup: Option<Popup>,
+    // columns of text in the detail view as last drawn, long lines wrap
+    pub details_width: u16,
+    pub status: Option<Status>,
+    pub process_table: TableState,
+    // pid of the highlighted row, used to keep the selection on the same
+    // process when the sorted order changes between refreshes
+    pub selected_pid: Option<Pid>,
+    // pids in the order they were last rendered
+    pub process_pids: Vec<Pid>,
+    // number of rows visible in the process table, used for PgUp/PgDn
+    pub page_size: usize,
+    pub sort_column: SortColumn,
+    pub sort_descending: bool,
+    pub prompt: Option<Prompt>,
+    // processes using less memory than this (in bytes) are hidden
+    pub min_memory: u64,
+    // when set, show the first `top_n` processes by the sort column instead
+    // of applying the memory threshold
+    pub top_mode: bool,
+    pub top_n: usize,
+    // applied while typing, so the table narrows with every key press
+    pub filter: Option<ProcessFilter>,
+    pub filter_text: String,
+    // only show processes of this user, applied on top of `filter`
+    pub user_filter: Option<ProcessFilter>,
+    pub tree_view: bool,
+    // pids whose children are hidden in tree view
+    pub collapsed: HashSet<Pid>,
+    // show the command line instead of the name, scrolled by `name_scroll` chars
+    pub show_command: bool,
+    pub name_scroll: usize,
+    pub users: UserCache,
+    // time between refreshes of the system stats
+    pub interval: Duration,
+    // no new samples are taken while paused, the last one stays on screen
+    pub paused: bool,
+    pub history: History,
+    // the disks of the disk panel, without the hidden file systems
+    pub disks: DiskList,
+    pub disk_view: DiskView,
+    // index of the first disk shown
+    pub disk_scroll: usize,
+    pub hidden_panels: HashSet<Panel>,
+    // drawn alone over the whole screen, regardless of `hidden_panels`
+    pub zoomed: Option<Panel>,
+    // set by the zoom key, the next panel key zooms instead of toggling
+    pub zoom_pending: bool,
+    // leaves loopback, bridges and other virtual interfaces out of the
+    // network panel
+    pub hide_virtual_networks: bool,
+    // process table columns, in display order
+    pub columns: Vec<SortColumn>,
+    pub theme: Theme,
+    pub keys: KeyBindings,
+}
+
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+pub const MIN_INTERVAL: Duration = Duration::from_millis(250);
+pub const MAX_INTERVAL: Duration = Duration::from_secs(60);
+// refresh interval from a number of milliseconds given by the user
+pub fn parse_interval(millis: u64) -> Result<Duration, String> {
+    let interval = Duration::from_millis(millis);
+    if (MIN_INTERVAL..=MAX_INTERVAL).contains(&interval) {
+        Ok(interval)
+    } else {
+        Err(format!(
+            "must be between {} and {} ms",
+            MIN_INTERVAL.as_millis(),
+            MAX_INTERVAL.as_millis()
+        ))
+    }
+}
+
+// intervals the speed up/slow down keys step through
+const INTERVAL_STEPS: [Duration; 9] = [
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_secs(2),
+    Duration::from_secs(3),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+    Duration::from_secs(30),
+    Duration::from_secs(60),
+];
+pub const DEFAULT_MIN_MEMORY: u64 = 50_000_000;
+pub const DEFAULT_TOP_N: usize = 25;
+// amount +/- changes the threshold (bytes) or the number of top processes by
+const MIN_MEMORY_STEP: u64 = 10_000_000;
+const TOP_N_STEP: usize = 5;
+// characters [/] scroll the command column by
+const NAME_SCROLL_STEP: usize = 8;
+
+impl App {
+    pub fn new() -> Self {
+        App {
+            running: true,
+            popup: None,
+            details_width: u16::MAX,
+            status: None,
+            process_table: TableState::default(),
+            selected_pid: None,
+            process_pids: Vec::new(),
+            page_size: 1,
+            sort_column: SortColumn::Memory,
+            sort_descending: true,
+            prompt: None,
+            min_memory: DEFAULT_MIN_MEMORY,
+            top_mode: false,
+            top_n: DEFAULT_TOP_N,
+            filter: None,
+            filter_text: String::new(),
+            user_filter: None,
+            tree_view: false,
+            collapsed: HashSet::new(),
+            show_command: false,
+            name_scroll: 0,
+            users: UserCache::new(),
+            interval: DEFAULT_INTERVAL,
+            paused: false,
+            history: History::new(DEFAULT_HISTORY),
+            disks: DiskList::default(),
+            disk_view: DiskView::Detailed,
+            disk_scroll: 0,
+            hidden_panels: HashSet::new(),
+            zoomed: None,
+            zoom_pending: false,
+            hide_virtual_networks: false,
+            columns: SortColumn::ALL.to_vec(),
+            theme: Theme::default(),
+            keys: KeyBindings::default(),
+        }
+    }
+
+    // `sys` is the ui thread's own System, used to look up single processes
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn handle_key(&mut self, key: KeyEvent, sys: &mut System) {
+        // raw mode turns ctrl+c into a key press instead of SIGINT
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.running = false;
+            return;
+        }
+        // messages last until the next key, errors of the filter being
+        // typed are set again below
+        self.status = None;
+        if let Some(popup) = self.popup.take() {
+            self.popup =
+                Self::handle_popup_key(popup, key, sys, self.details_width, &mut self.status);
+            return;
+        }
+        if let Some(prompt) = self.prompt.take() {
+            self.handle_prompt_key(prompt, key);
+            return;
+        }
+
+        let Some(action) = self.keys.action(key.code) else {
+            self.zoom_pending = false;
+            return;
+        };
+        // any other key cancels picking a panel to zoom
+        let zoom_pending = std::mem::take(&mut self.zoom_pending);
+        match action {
+            // backs out of picking a panel to zoom
+            Action::Quit if zoom_pending => {}
+            Action::Quit => self.running = false,
+            Action::Signal => self.open_signal_picker(sys),
+            Action::Details => {
+                if let Some(pid) = self.selected_pid {
+                    collector::refresh_process(sys, pid);
+                    self.popup = Some(Popup::Details { pid, scroll: 0 });
+                }
+            }
+            Action::SortNext => self.cycle_sort(true),
+            Action::SortPrev => self.cycle_sort(false),
+            Action::Reverse => self.sort_descending = !self.sort_descending,
+            Action::Grow => self.grow_limit(),
+            Action::Shrink => self.shrink_limit(),
+            Action::TopMode => self.top_mode = !self.top_mode,
+            Action::Faster => self.change_interval(false),
+            Action::Slower => self.change_interval(true),
+            Action::Pause => self.paused = !self.paused,
+            Action::TogglePanel(panel) if zoom_pending => self.zoomed = Some(panel),
+            Action::TogglePanel(panel) => {
+                // leaves the zoom so the change is visible
+                self.zoomed = None;
+                if !self.hidden_panels.remove(&panel) {
+                    self.hidden_panels.insert(panel);
+                }
+            }
+            Action::VirtualNetworks => {
+                self.hide_virtual_networks = !self.hide_virtual_networks;
+            }
+            Action::DiskView => self.disk_view = self.disk_view.next(),
+            Action::ScrollDisksUp => self.disk_scroll = self.disk_scroll.saturating_sub(1),
+            Action::ScrollDisksDown => {
+                let count = self.disks.entries(Instant::now()).len();
+                self.disk_scroll = (self.disk_scroll + 1).min(count.saturating_sub(1));
+            }
+            Action::Zoom => {
+                if self.zoomed.take().is_none() {
+                    self.zoom_pending = !zoom_pending;
+                }
+            }
+            Action::Tree => self.tree_view = !self.tree_view,
+            Action::Command => {
+                self.show_command = !self.show_command;
+                self.name_scroll = 0;
+            }
+            Action::ScrollLeft if self.show_command => {
+                self.name_scroll = self.name_scroll.saturating_sub(NAME_SCROLL_STEP);
+            }
+            Action::ScrollRight if self.show_command => {
+                self.name_scroll = self.name_scroll.saturating_add(NAME_SCROLL_STEP);
+            }
+            Action::Collapse if self.tree_view => self.set_collapsed(true),
+            Action::Expand if self.tree_view => self.set_collapsed(false),
+            Action::ToggleCollapse if self.tree_view => {
+                let collapsed = self
+                    .selected_pid
+                    .is_some_and(|pid| self.collapsed.contains(&pid));
+                self.set_collapsed(!collapsed);
+            }
+            Action::Filter => {
+                self.prompt = Some(Prompt {
+                    kind: PromptKind::Filter,
+                    input: self.filter_text.clone(),
+                });
+            }
+            Action::Threshold => {
+                self.prompt = Some(Prompt {
+                    kind: PromptKind::Threshold,
+                    input: String::new(),
+                });
+            }
+            Action::Up => self.move_selection(-1),
+            Action::Down => self.move_selection(1),
+            Action::PageUp => self.move_selection(-(self.page_size as isize)),
+            Action::PageDown => self.move_selection(self.page_size as isize),
+            Action::Home => self.select_index(0),
+            Action::End => self.select_index(self.process_pids.len().saturating_sub(1)),
+            Action::ScrollLeft
+            | Action::ScrollRight
+            | Action::Collapse
+            | Action::Expand
+            | Action::ToggleCollapse => {}
+        }
+    }
+
+    pub fn shows(&self, panel: Panel) -> bool {
+        match self.zoomed {
+            Some(zoomed) => zoomed == panel,
+            None => !self.hidden_panels.contains(&panel),
+        }
+    }
+
+    pub fn prompt_label(&self, kind: &PromptKind) -> &'static str {
+        match kind {
+            PromptKind::Threshold if self.top_mode => "Show top N processes: ",
+            PromptKind::Threshold => "Min memory (MB): ",
+            PromptKind::Filter => "Filter (text, re:, pid:, user:): ",
+        }
+    }
+
+    fn handle_prompt_key(&mut self, mut prompt: Prompt, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                // escape clears the filter instead of keeping what was typed
+                if matches!(prompt.kind, PromptKind::Filter) {
+                    self.set_filter_or_report(String::new());
+                }
+            }
+            KeyCode::Enter => self.apply_prompt(&prompt),
+            KeyCode::Backspace => {
+                prompt.input.pop();
+                self.update_prompt(prompt);
+            }
+            KeyCode::Char(c) => {
+                prompt.input.push(c);
+                self.update_prompt(prompt);
+            }
+            _ => self.prompt = Some(prompt),
+        }
+    }
+
+    // steps through the shown columns only, so the sorted one is always
+    // marked in the header
+    fn cycle_sort(&mut self, forward: bool) {
+        let len = self.columns.len();
+        let next = match self.columns.iter().position(|c| *c == self.sort_column) {
+            Some(i) if forward => (i + 1) % len,
+            Some(i) => (i + len - 1) % len,
+            None => 0,
+        };
+        self.sort_column = self.columns[next];
+    }
+
+    // keeps the prompt open after its input changed
+    fn update_prompt(&mut self, prompt: Prompt) {
+        if matches!(prompt.kind, PromptKind::Filter) {
+            self.set_filter_or_report(prompt.input.clone());
+        }
+        self.prompt = Some(prompt);
+    }
+
+    // an invalid filter (e.g. a half typed regex) keeps the last valid one
+    pub fn set_filter(&mut self, text: String) -> Result<(), String> {
+        self.filter = ProcessFilter::parse(&text)?;
+        self.filter_text = text;
+        Ok(())
+    }
+
+    // like set_filter, but shows errors in the status line
+    fn set_filter_or_report(&mut self, text: String) {
+        self.status = self.set_filter(text).err().map(Status::Error);
+    }
+
+    pub fn set_user_filter(&mut self, user: Option<String>) {
+        self.user_filter = user.map(ProcessFilter::User);
+    }
+
+    // whether the process passes both the typed filter and the user filter
+    pub fn matches(&self, process: &ProcessInfo) -> bool {
+        [&self.filter, &self.user_filter]
+            .into_iter()
+            .flatten()
+            .all(|filter| filter.matches(process, &self.users))
+    }
+
+    fn apply_prompt(&mut self, prompt: &Prompt) {
+        match prompt.kind {
+            PromptKind::Filter => self.set_filter_or_report(prompt.input.clone()),
+            PromptKind::Threshold => {
+                let Ok(value) = prompt.input.trim().parse::<u64>() else {
+                    self.status =
+                        Some(Status::Error(format!("Invalid number: {:?}", prompt.input)));
+                    return;
+                };
+                if self.top_mode {
+                    self.top_n = usize::try_from(value).unwrap_or(usize::MAX).max(1);
+                } else {
+                    self.min_memory = value.saturating_mul(1_000_000);
+                }
+            }
+        }
+    }
+
+    // collapses or expands the subtree of the selected process
+    fn set_collapsed(&mut self, collapsed: bool) {
+        let Some(pid) = self.selected_pid else {
+            return;
+        };
+        if collapsed {
+            self.collapsed.insert(pid);
+        } else {
+            self.collapsed.remove(&pid);
+        }
+    }
+
+    fn grow_limit(&mut self) {
+        if self.top_mode {
+            self.top_n = self.top_n.saturating_add(TOP_N_STEP);
+        } else {
+            self.min_memory = self.min_memory.saturating_add(MIN_MEMORY_STEP);
+        }
+    }
+
+    fn shrink_limit(&mut self) {
+        if self.top_mode {
+            self.top_n = self.top_n.saturating_sub(TOP_N_STEP).max(1);
+        } else {
+            self.min_memory = self.min_memory.saturating_sub(MIN_MEMORY_STEP);
+        }
+    }
+
+    // moves the interval to the next longer or shorter step, an interval set
+    // in between steps moves to the nearest step in that direction
+    fn change_interval(&mut self, slower: bool) {
+        let step = if slower {
+            INTERVAL_STEPS.iter().find(|step| **step > self.interval)
+        } else {
+            INTERVAL_STEPS
+                .iter()
+                .rev()
+                .find(|step| **step < self.interval)
+        };
+        if let Some(step) = step {
+            self.interval = *step;
+        }
+    }
+
+    // returns the popup that should be open after the key press
+    fn handle_popup_key(
+        popup: Popup,
+        key: KeyEvent,
+        sys: &mut System,
+        details_width: u16,
+        status: &mut Option<Status>,
+    ) -> Option<Popup> {
+        match popup {
+            Popup::SignalPicker { target, selected } => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => None,
+                KeyCode::Up | KeyCode::Char('k') => Some(Popup::SignalPicker {
+                    target,
+                    selected: selected.saturating_sub(1),
+                }),
+                KeyCode::Down | KeyCode::Char('j') => Some(Popup::SignalPicker {
+                    target,
+                    selected: (selected + 1).min(SIGNALS.len() - 1),
+                }),
+                KeyCode::Enter => Some(Popup::ConfirmSignal { target, selected }),
+                _ => Some(Popup::SignalPicker { target, selected }),
+            },
+            Popup::ConfirmSignal { target, selected } => match key.code {
+                KeyCode::Char('y' | 'Y') | KeyCode::Enter => {
+                    *status = Some(match signals::send_signal(sys, &target, selected) {
+                        Ok(msg) => Status::Info(msg),
+                        Err(msg) => Status::Error(msg),
+                    });
+                    None
+                }
+                KeyCode::Char('n' | 'N' | 'q') | KeyCode::Esc => None,
+                _ => Some(Popup::ConfirmSignal { target, selected }),
+            },
+            Popup::Details { pid, scroll } => {
+                let max_scroll =
+                    u16::try_from(details::line_count(sys, pid, details_width).saturating_sub(1))
+                        .unwrap_or(u16::MAX);
+                let scroll = match key.code {
+                    KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => return None,
+                    KeyCode::Up | KeyCode::Char('k') => scroll.saturating_sub(1),
+                    KeyCode::Down | KeyCode::Char('j') => scroll.saturating_add(1),
+                    KeyCode::PageUp => scroll.saturating_sub(10),
+                    KeyCode::PageDown => scroll.saturating_add(10),
+                    KeyCode::Home => 0,
+                    KeyCode::End => max_scroll,
+                    _ => scroll,
+                };
+                Some(Popup::Details {
+                    pid,
+                    scroll: scroll.min(max_scroll),
+                })
+            }
+        }
+    }
+
+    fn open_signal_picker(&mut self, sys: &mut System) {
+        let Some(pid) = self.selected_pid else {
+            return;
+        };
+        sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+        let name = sys
+            .process(pid)
+            .map(|process| process.name().to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.popup = Some(Popup::SignalPicker {
+            target: SignalTarget { pid, name },
+            selected: 0,
+        });
+    }
+
+    // replace the rendered pid order and move the highlighted row so it stays
+    // on the previously selected pid, if that process still exists
+    pub fn sync_processes(&mut self, pids: Vec<Pid>) {
+        self.process_pids = pids;
+        if self.process_pids.is_empty() {
+            self.selected_pid = None;
+            self.process_table.select(None);
+            return;
+        }
+
+        let index = self
+            .selected_pid
+            .and_then(|pid| self.process_pids.iter().position(|p| *p == pid))
+            .unwrap_or_else(|| {
+                // the selected process exited (or nothing was selected yet),
+                // keep the cursor at the same height in the table
+                self.process_table
+                    .selected()
+                    .unwrap_or(0)
+                    .min(self.process_pids.len() - 1)
+            });
+        self.select_index(index);
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let current = self.process_table.selected().unwrap_or(0);
+        self.select_index(current.saturating_add_signed(delta));
+    }
+
+    fn select_index(&mut self, index: usize) {
+        if self.process_pids.is_empty() {
+            return;
+        }
+        let index = index.min(self.process_pids.len() - 1);
+        self.process_table.select(Some(index));
+        self.selected_pid = Some(self.process_pids[index]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interval_steps() {
+        let mut app = App::new();
+        app.change_interval(false);
+        assert_eq!(app.interval, Duration::from_millis(500));
+        app.change_interval(false);
+        app.change_interval(false);
+        assert_eq!(app.interval, MIN_INTERVAL);
+
+        // an interval between steps moves to the next step in either direction
+        app.interval = Duration::from_millis(1_500);
+        app.change_interval(true);
+        assert_eq!(app.interval, Duration::from_secs(2));
+        app.interval = Duration::from_millis(1_500);
+        app.change_interval(false);
+        assert_eq!(app.interval, Duration::from_secs(1));
+
+        app.interval = MAX_INTERVAL;
+        app.change_interval(true);
+        assert_eq!(app.interval, MAX_INTERVAL);
+    }
+
+    #[test]
+    fn interval_range() {
+        assert!(parse_interval(249).is_err());
+        assert_eq!(parse_interval(250), Ok(MIN_INTERVAL));
+        assert_eq!(parse_interval(60_000), Ok(MAX_INTERVAL));
+        assert!(parse_interval(60_001).is_err());
+    }
+
+    #[test]
+    fn status_cleared_by_next_key() {
+        let mut app = App::new();
+        let mut sys = System::new();
+        app.status = Some(Status::Info("Sent SIGTERM to 42".to_string()));
+        app.handle_key(KeyEvent::from(KeyCode::Char('m')), &mut sys);
+        assert!(app.status.is_none());
+
+        // an invalid threshold is reported until the next key
+        app.prompt.as_mut().unwrap().input = "abc".to_string();
+        app.handle_key(KeyEvent::from(KeyCode::Enter), &mut sys);
+        assert!(matches!(app.status, Some(Status::Error(_))));
+        app.handle_key(KeyEvent::from(KeyCode::Char('r')), &mut sys);
+        assert!(app.status.is_none());
+    }
+
+    #[test]
+    fn sort_cycles_through_shown_columns() {
+        let mut app = App::new();
+        app.columns = vec![SortColumn::Pid, SortColumn::Memory, SortColumn::Cpu];
+        app.sort_column = SortColumn::Cpu;
+        app.cycle_sort(true);
+        assert_eq!(app.sort_column, SortColumn::Pid);
+        app.cycle_sort(false);
+        assert_eq!(app.sort_column, SortColumn::Cpu);
+    }
+
+    #[test]
+    fn toggle_and_zoom_panels() {
+        let mut app = App::new();
+        let mut sys = System::new();
+        let mut press =
+            |app: &mut App, c| app.handle_key(KeyEvent::from(KeyCode::Char(c)), &mut sys);
+
+        press(&mut app, '3');
+        assert!(!app.shows(Panel::Swap));
+        press(&mut app, '3');
+        assert!(app.shows(Panel::Swap));
+
+        // z then a panel key zooms it, z again goes back
+        press(&mut app, 'z');
+        press(&mut app, '1');
+        assert_eq!(app.zoomed, Some(Panel::Cpu));
+        assert!(!app.shows(Panel::Processes));
+        press(&mut app, 'z');
+        assert_eq!(app.zoomed, None);
+        assert!(!app.zoom_pending);
+
+        // any other key cancels picking a panel
+        press(&mut app, 'z');
+        press(&mut app, 'r');
+        press(&mut app, '1');
+        assert_eq!(app.zoomed, None);
+        assert!(!app.shows(Panel::Cpu));
+    }
+}
@@ -0,0 +1,112 @@
+use crate::app::{self, App};
+use crate::history::{self, History};
+use crate::layout::Panel;
+use crate::processes::SortColumn;
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use std::path::PathBuf;
+
+/// A terminal-based resource manager for monitoring system resources and processes
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Time between refreshes in milliseconds, from 250 to 60000
+    #[arg(long, value_name = "MS")]
+    pub interval: Option<u64>,
+
+    /// Hide processes using less memory than this many MB
+    #[arg(long, value_name = "MB")]
+    pub min_mem: Option<u64>,
+
+    /// Show the top N processes by the sort column instead of using a memory threshold
+    #[arg(long, value_name = "N")]
+    pub top: Option<usize>,
+
+    /// Column to sort processes by: pid, name, mem, cpu, read, write, uptime or user
+    #[arg(long, value_name = "COLUMN")]
+    pub sort: Option<SortColumn>,
+
+    /// Sort in ascending instead of descending order
+    #[arg(long)]
+    pub ascending: bool,
+
+    /// Initial process filter, same syntax as the / prompt
+    #[arg(long, value_name = "FILTER")]
+    pub filter: Option<String>,
+
+    /// Only show processes of this user (name or uid)
+    #[arg(long, value_name = "USER")]
+    pub user: Option<String>,
+
+    /// Number of samples shown in the history graphs
+    #[arg(long, value_name = "SAMPLES")]
+    pub history: Option<usize>,
+
+    /// Hide the process table
+    #[arg(long)]
+    pub no_processes: bool,
+
+    /// Hide the disk panel
+    #[arg(long)]
+    pub no_disks: bool,
+
+    /// Read settings from this file instead of the default location
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+}
+
+impl Cli {
+    // overrides the app defaults with the options given on the command line,
+    // exits with a usage error if a value is invalid
+    pub fn apply(&self, app: &mut App) {
+        if let Some(interval) = self.interval {
+            match app::parse_interval(interval) {
+                Ok(interval) => app.interval = interval,
+                Err(msg) => invalid(&format!("--interval {msg}")),
+            }
+        }
+        if let Some(min_mem) = self.min_mem {
+            app.min_memory = min_mem.saturating_mul(1_000_000);
+        }
+        if let Some(top) = self.top {
+            if top == 0 {
+                invalid("--top must be greater than 0");
+            }
+            app.top_n = top;
+            app.top_mode = true;
+        }
+        if let Some(sort) = self.sort {
+            // the config file may hide some columns
+            if !app.columns.contains(&sort) {
+                invalid(&format!("--sort: {} is not a shown column", sort.header()));
+            }
+            app.sort_column = sort;
+        }
+        if self.ascending {
+            app.sort_descending = false;
+        }
+        if let Some(filter) = &self.filter {
+            if let Err(msg) = app.set_filter(filter.clone()) {
+                invalid(&format!("--filter: {msg}"));
+            }
+        }
+        if let Some(user) = &self.user {
+            app.set_user_filter(Some(user.clone()));
+        }
+        if let Some(history) = self.history {
+            match history::parse_history(history) {
+                Ok(len) => app.history = History::new(len),
+                Err(msg) => invalid(&format!("--history {msg}")),
+            }
+        }
+        if self.no_processes {
+            app.hidden_panels.insert(Panel::Processes);
+        }
+        if self.no_disks {
+            app.hidden_panels.insert(Panel::Disks);
+        }
+    }
+}
+
+pub fn invalid(msg: &str) -> ! {
+    Cli::command().error(ErrorKind::ValueValidation, msg).exit()
+}
@@ -0,0 +1,347 @@
+use crate::diskstats::{DiskIoSample, DiskStats};
+use crate::events::AppEvent;
+use crate::processes::ProcessInfo;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::{
+    DiskRefreshKind, Disks, NetworkData, Networks, Pid, Process, ProcessRefreshKind,
+    ProcessesToUpdate, System, UpdateKind,
+};
+
+// how often the mounted disks are listed again, their usage is refreshed
+// with every sample
+const DISK_LIST_INTERVAL: Duration = Duration::from_secs(5);
+
+pub struct CpuSample {
+    pub name: String,
+    pub usage: f32,
+}
+
+#[derive(Clone)]
+pub struct DiskSample {
+    pub name: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub kind: String,
+    pub total_space: u64,
+    pub available_space: u64,
+}
+
+pub struct NetworkSample {
+    pub name: String,
+    // bytes per second since the previous sample
+    pub receive_rate: u64,
+    pub transmit_rate: u64,
+    // totals since the interface came up
+    pub total_received: u64,
+    pub total_transmitted: u64,
+    pub packets_received: u64,
+    pub packets_transmitted: u64,
+    pub errors_received: u64,
+    pub errors_transmitted: u64,
+    pub mac_address: String,
+    // "192.168.1.2/24"
+    pub addresses: Vec<String>,
+    // loopback, bridges, veths, tunnels and the like
+    pub is_virtual: bool,
+}
+
+// bytes per second from the bytes counted over `elapsed`
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+pub fn rate(bytes: u64, elapsed: Duration) -> u64 {
+    if elapsed.is_zero() {
+        0
+    } else {
+        (bytes as f64 / elapsed.as_secs_f64()) as u64
+    }
+}
+
+impl NetworkSample {
+    fn new(name: &str, data: &NetworkData, elapsed: Duration) -> Self {
+        // ipv4 first, in a stable order
+        let mut networks = data.ip_networks().to_vec();
+        networks.sort_by_key(|network| network.addr);
+        let addresses = networks
+            .iter()
+            .map(|network| format!("{}/{}", network.addr, network.prefix))
+            .collect();
+        NetworkSample {
+            name: name.to_string(),
+            receive_rate: rate(data.received(), elapsed),
+            transmit_rate: rate(data.transmitted(), elapsed),
+            total_received: data.total_received(),
+            total_transmitted: data.total_transmitted(),
+            packets_received: data.total_packets_received(),
+            packets_transmitted: data.total_packets_transmitted(),
+            errors_received: data.total_errors_on_received(),
+            errors_transmitted: data.total_errors_on_transmitted(),
+            mac_address: data.mac_address().to_string(),
+            addresses,
+            is_virtual: is_virtual_interface(name),
+        }
+    }
+}
+
+// on linux only interfaces backed by a device have a device link in sysfs,
+// elsewhere the usual names of virtual interfaces are matched
+fn is_virtual_interface(name: &str) -> bool {
+    let sysfs = Path::new("/sys/class/net").join(name);
+    if sysfs.exists() {
+        return !sysfs.join("device").exists();
+    }
+    const VIRTUAL_PREFIXES: [&str; 11] = [
+        "lo", "docker", "veth", "br-", "virbr", "vmnet", "vboxnet", "tun", "tap", "utun", "bridge",
+    ];
+    VIRTUAL_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+pub struct SystemInfo {
+    pub host_name: Option<String>,
+    pub os_version: Option<String>,
+    pub os_name: Option<String>,
+    pub arch: String,
+    pub uptime: u64,
+}
+
+// everything the main view draws, taken at one point in time by the
+// collector thread and never changed afterwards
+pub struct Snapshot {
+    pub taken: Instant,
+    pub global_cpu_usage: f32,
+    pub cpus: Vec<CpuSample>,
+    pub total_memory: u64,
+    pub used_memory: u64,
+    pub available_memory: u64,
+    pub free_memory: u64,
+    pub total_swap: u64,
+    pub used_swap: u64,
+    pub free_swap: u64,
+    pub disks: Vec<DiskSample>,
+    pub disk_io: Vec<DiskIoSample>,
+    // sorted by name
+    pub networks: Vec<NetworkSample>,
+    pub processes: Vec<ProcessInfo>,
+    pub system: SystemInfo,
+}
+
+impl Snapshot {
+    // `elapsed` is the time since the previous sample, which the network and
+    // process byte counts are relative to
+    fn take(
+        sys: &System,
+        disks: &Disks,
+        disk_io: Vec<DiskIoSample>,
+        networks: &Networks,
+        elapsed: Duration,
+    ) -> Self {
+        let mut networks: Vec<NetworkSample> = networks
+            .list()
+            .iter()
+            .map(|(name, data)| NetworkSample::new(name, data, elapsed))
+            .collect();
+        networks.sort_by(|a, b| a.name.cmp(&b.name));
+        Snapshot {
+            taken: Instant::now(),
+            global_cpu_usage: sys.global_cpu_usage(),
+            cpus: sys
+                .cpus()
+                .iter()
+                .map(|cpu| CpuSample {
+                    name: cpu.name().to_string(),
+                    usage: cpu.cpu_usage(),
+                })
+                .collect(),
+            total_memory: sys.total_memory(),
+            used_memory: sys.used_memory(),
+            available_memory: sys.available_memory(),
+            free_memory: sys.free_memory(),
+            total_swap: sys.total_swap(),
+            used_swap: sys.used_swap(),
+            free_swap: sys.free_swap(),
+            disks: disks
+                .list()
+                .iter()
+                .map(|disk| DiskSample {
+                    name: disk.name().to_string_lossy().to_string(),
+                    mount_point: disk.mount_point().display().to_string(),
+                    file_system: disk.file_system().to_string_lossy().to_string(),
+                    kind: disk.kind().to_string(),
+                    total_space: disk.total_space(),
+                    available_space: disk.available_space(),
+                })
+                .collect(),
+            disk_io,
+            networks,
+            processes: sys
+                .processes()
+                .values()
+                .map(|process| ProcessInfo::new(process, elapsed))
+                .collect(),
+            system: SystemInfo {
+                host_name: System::host_name(),
+                os_version: System::os_version(),
+                os_name: System::name(),
+                arch: System::cpu_arch(),
+                uptime: System::uptime(),
+            },
+        }
+    }
+}
+
+// how often the collector samples and whether it samples at all
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Schedule {
+    pub interval: Duration,
+    pub paused: bool,
+}
+
+pub struct Collector {
+    schedule: Sender<Schedule>,
+}
+
+impl Collector {
+    // takes effect right away, a shorter interval does not wait for the
+    // longer deadline that was already scheduled
+    pub fn set_schedule(&self, schedule: Schedule) {
+        // fails only if the thread stopped, the last snapshot stays on screen
+        let _ = self.schedule.send(schedule);
+    }
+}
+
+// starts the thread sampling the system, the ui only ever reads the snapshots
+// it sends so a slow refresh cannot block key handling. the thread stops once
+// the collector or the receiving end of `events` is dropped
+pub fn spawn(schedule: Schedule, events: Sender<AppEvent>) -> Collector {
+    let (schedule_sender, schedule_receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let stopped = Stopped(events);
+        run(schedule, &schedule_receiver, &stopped.0);
+    });
+    Collector {
+        schedule: schedule_sender,
+    }
+}
+
+// tells the main loop that no more snapshots are coming, also when sampling
+// panicked. the channel itself stays open since the input thread holds a
+// sender as well
+struct Stopped(Sender<AppEvent>);
+
+impl Drop for Stopped {
+    fn drop(&mut self) {
+        let _ = self.0.send(AppEvent::CollectorStopped);
+    }
+}
+
+fn run(mut schedule: Schedule, changes: &Receiver<Schedule>, events: &Sender<AppEvent>) {
+    let mut sys = System::new_all();
+    // disk i/o comes from `DiskStats`, sysinfo would read /proc/diskstats again
+    // for every disk
+    let mut disks = Disks::new_with_refreshed_list_specifics(
+        DiskRefreshKind::nothing().with_kind().with_storage(),
+    );
+    let mut disks_listed = Instant::now();
+    let mut disk_stats = DiskStats::new();
+    let mut networks = Networks::new_with_refreshed_list();
+    let mut last_sample = Instant::now();
+    let mut started = Instant::now();
+    let mut deadline = started;
+    loop {
+        // wait for the deadline, or indefinitely while paused
+        while schedule.paused || Instant::now() < deadline {
+            let change = if schedule.paused {
+                changes.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            } else {
+                changes.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            };
+            match change {
+                Ok(change) => {
+                    // resuming samples right away, otherwise the deadline is
+                    // moved to match the new interval
+                    deadline = if schedule.paused {
+                        Instant::now()
+                    } else {
+                        started + change.interval
+                    };
+                    schedule = change;
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+
+        started = Instant::now();
+        sys.refresh_cpu_all();
+        sys.refresh_memory();
+        // only what the table shows, the details view refreshes the rest
+        // for its own process
+        sys.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing()
+                .with_memory()
+                .with_cpu()
+                .with_cmd(UpdateKind::Always)
+                .with_user(UpdateKind::Always)
+                .with_disk_usage(),
+        );
+        // new mounts show up and unmounted disks go away, reading the mount
+        // table every time would be wasted on short intervals
+        if disks_listed.elapsed() >= DISK_LIST_INTERVAL {
+            disks.refresh_specifics(true, DiskRefreshKind::nothing().with_kind().with_storage());
+            disks_listed = Instant::now();
+        } else {
+            for disk in disks.list_mut() {
+                disk.refresh_specifics(DiskRefreshKind::nothing().with_storage());
+            }
+        }
+        // also picks up interfaces that came up since
+        networks.refresh(true);
+        let elapsed = last_sample.elapsed();
+        last_sample = Instant::now();
+        let disk_io = disk_stats.sample(elapsed);
+        let snapshot = Snapshot::take(&sys, &disks, disk_io, &networks, elapsed);
+        if events.send(AppEvent::Snapshot(Box::new(snapshot))).is_err() {
+            return;
+        }
+
+        // deadlines are spaced by the interval no matter how long sampling
+        // took, so refreshes do not drift. if sampling took longer than the
+        // interval the missed deadlines are skipped
+        deadline += schedule.interval;
+        if deadline < Instant::now() {
+            deadline = Instant::now() + schedule.interval;
+        }
+    }
+}
+
+// refreshes a single process and its threads in `sys`, used by the ui thread
+// for the details view and for sending signals, which need more than the
+// snapshot holds
+pub fn refresh_process(sys: &mut System, pid: Pid) -> Option<&Process> {
+    let refresh = |sys: &mut System, pids: &[Pid]| {
+        sys.refresh_processes_specifics(
+            ProcessesToUpdate::Some(pids),
+            true,
+            ProcessRefreshKind::everything(),
+        );
+    };
+    refresh(sys, &[pid]);
+    let tasks: Vec<Pid> = sys
+        .process(pid)?
+        .tasks()
+        .map(|tasks| tasks.iter().copied().collect())
+        .unwrap_or_default();
+    if !tasks.is_empty() {
+        refresh(sys, &tasks);
+    }
+    sys.process(pid)
+}
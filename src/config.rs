@@ -0,0 +1,404 @@
+use crate::app::{self, App};
+use crate::disks::DiskView;
+use crate::history::{self, History};
+use crate::keys::KeyBindings;
+use crate::layout::Panel;
+use crate::processes::SortColumn;
+use crate::theme::{parse_color, Theme};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fmt, fs, io};
+
+// config.toml as written, load() checks it and turns it into a Config
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    // milliseconds between refreshes
+    interval: Option<u64>,
+    // MB
+    min_mem: Option<u64>,
+    top: Option<usize>,
+    sort: Option<String>,
+    ascending: Option<bool>,
+    columns: Option<Vec<String>>,
+    // samples shown in the history graphs
+    history: Option<usize>,
+    show_processes: Option<bool>,
+    show_disks: Option<bool>,
+    // panels hidden at startup, on top of show_processes and show_disks
+    hidden_panels: Option<Vec<String>>,
+    hide_virtual_networks: Option<bool>,
+    // file systems left out of the disk panel, e.g. "tmpfs" or "squashfs"
+    hidden_filesystems: Option<Vec<String>>,
+    disk_view: Option<String>,
+    theme: Option<String>,
+    thresholds: Option<Thresholds>,
+    colors: Option<Colors>,
+    #[serde(default)]
+    keys: HashMap<String, KeyList>,
+}
+
+// the settings of config.toml, parsed and checked. anything left out keeps its
+// default and command line options take precedence over all of it
+#[derive(Default)]
+pub struct Config {
+    interval: Option<Duration>,
+    // bytes
+    min_memory: Option<u64>,
+    top: Option<usize>,
+    sort: Option<SortColumn>,
+    ascending: Option<bool>,
+    columns: Option<Vec<SortColumn>>,
+    history: Option<usize>,
+    // including show_processes and show_disks
+    hidden_panels: Vec<Panel>,
+    hide_virtual_networks: Option<bool>,
+    hidden_filesystems: Option<Vec<String>>,
+    disk_view: Option<DiskView>,
+    theme: Theme,
+    keys: KeyBindings,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Thresholds {
+    warning: Option<f32>,
+    critical: Option<f32>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Colors {
+    ok: Option<String>,
+    warning: Option<String>,
+    critical: Option<String>,
+    border: Option<String>,
+    header: Option<String>,
+    highlight: Option<String>,
+    graph: Option<String>,
+}
+
+// a key binding can be a single key or a list of keys
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, String),
+    Invalid(PathBuf, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => write!(f, "{}: {err}", path.display()),
+            ConfigError::Parse(path, msg) | ConfigError::Invalid(path, msg) => {
+                write!(f, "{}: {msg}", path.display())
+            }
+        }
+    }
+}
+
+// $XDG_CONFIG_HOME/resource-manager/config.toml, falling back to ~/.config
+pub fn default_path() -> Option<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("resource-manager").join("config.toml"))
+}
+
+// reads `path` if given, otherwise the default location; a missing default
+// config file is not an error
+pub fn load(path: Option<&Path>) -> Result<Config, ConfigError> {
+    let (path, required) = match path {
+        Some(path) => (path.to_path_buf(), true),
+        None => match default_path() {
+            Some(path) => (path, false),
+            None => return Ok(Config::default()),
+        },
+    };
+
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound && !required => {
+            return Ok(Config::default());
+        }
+        Err(err) => return Err(ConfigError::Io(path, err)),
+    };
+    let file: ConfigFile =
+        toml::from_str(&text).map_err(|err| ConfigError::Parse(path.clone(), err.to_string()))?;
+    file.parse().map_err(|msg| ConfigError::Invalid(path, msg))
+}
+
+impl ConfigFile {
+    // checks and converts every setting, so errors show up before the
+    // terminal is taken over
+    fn parse(self) -> Result<Config, String> {
+        let interval = self
+            .interval
+            .map(|interval| app::parse_interval(interval).map_err(|err| format!("interval {err}")))
+            .transpose()?;
+        if self.top == Some(0) {
+            return Err("top must be greater than 0".to_string());
+        }
+        let history = self
+            .history
+            .map(|len| history::parse_history(len).map_err(|err| format!("history {err}")))
+            .transpose()?;
+        let sort = self
+            .sort
+            .as_deref()
+            .map(|sort| {
+                sort.parse::<SortColumn>()
+                    .map_err(|err| format!("sort: {err}"))
+            })
+            .transpose()?;
+        let columns = self.columns()?;
+        if let (Some(sort), Some(columns)) = (sort, &columns) {
+            if !columns.contains(&sort) {
+                return Err(format!("sort: {} is not a shown column", sort.header()));
+            }
+        }
+        let disk_view = self
+            .disk_view
+            .as_deref()
+            .map(|view| {
+                view.parse::<DiskView>()
+                    .map_err(|err| format!("disk_view: {err}"))
+            })
+            .transpose()?;
+        let mut hidden_panels = self.hidden_panels()?;
+        if self.show_processes == Some(false) {
+            hidden_panels.push(Panel::Processes);
+        }
+        if self.show_disks == Some(false) {
+            hidden_panels.push(Panel::Disks);
+        }
+        let theme = self.theme()?;
+        let keys = self.key_bindings()?;
+
+        Ok(Config {
+            interval,
+            min_memory: self.min_mem.map(|mb| mb.saturating_mul(1_000_000)),
+            top: self.top,
+            sort,
+            ascending: self.ascending,
+            columns,
+            history,
+            hidden_panels,
+            hide_virtual_networks: self.hide_virtual_networks,
+            hidden_filesystems: self.hidden_filesystems,
+            disk_view,
+            theme,
+            keys,
+        })
+    }
+
+    fn columns(&self) -> Result<Option<Vec<SortColumn>>, String> {
+        let Some(columns) = &self.columns else {
+            return Ok(None);
+        };
+        if columns.is_empty() {
+            return Err("columns must list at least one column".to_string());
+        }
+        let mut parsed: Vec<SortColumn> = Vec::new();
+        for column in columns {
+            let column = column.parse().map_err(|err| format!("columns: {err}"))?;
+            if parsed.contains(&column) {
+                return Err(format!("columns: {} is listed twice", column.header()));
+            }
+            parsed.push(column);
+        }
+        Ok(Some(parsed))
+    }
+
+    fn hidden_panels(&self) -> Result<Vec<Panel>, String> {
+        self.hidden_panels
+            .iter()
+            .flatten()
+            .map(|panel| panel.parse().map_err(|err| format!("hidden_panels: {err}")))
+            .collect()
+    }
+
+    fn theme(&self) -> Result<Theme, String> {
+        let mut theme = Theme::preset(self.theme.as_deref().unwrap_or("default"))?;
+
+        if let Some(thresholds) = &self.thresholds {
+            if let Some(warning) = thresholds.warning {
+                theme.warning_threshold = warning;
+            }
+            if let Some(critical) = thresholds.critical {
+                theme.critical_threshold = critical;
+            }
+        }
+        let in_range = |value: f32| (0.0..=100.0).contains(&value);
+        if !in_range(theme.warning_threshold) || !in_range(theme.critical_threshold) {
+            return Err("thresholds must be between 0 and 100".to_string());
+        }
+        if theme.warning_threshold > theme.critical_threshold {
+            return Err("thresholds.warning must not be above thresholds.critical".to_string());
+        }
+
+        if let Some(colors) = &self.colors {
+            let slots = [
+                (&colors.ok, &mut theme.ok, "ok"),
+                (&colors.warning, &mut theme.warning, "warning"),
+                (&colors.critical, &mut theme.critical, "critical"),
+                (&colors.border, &mut theme.border, "border"),
+                (&colors.header, &mut theme.header, "header"),
+                (&colors.highlight, &mut theme.highlight, "highlight"),
+                (&colors.graph, &mut theme.graph, "graph"),
+            ];
+            for (name, slot, key) in slots {
+                if let Some(name) = name {
+                    *slot = parse_color(name).map_err(|err| format!("colors.{key}: {err}"))?;
+                }
+            }
+        }
+        Ok(theme)
+    }
+
+    fn key_bindings(&self) -> Result<KeyBindings, String> {
+        let overrides = self
+            .keys
+            .iter()
+            .map(|(action, keys)| {
+                let keys = match keys {
+                    KeyList::One(key) => vec![key.clone()],
+                    KeyList::Many(keys) => keys.clone(),
+                };
+                (action.clone(), keys)
+            })
+            .collect();
+        KeyBindings::new(&overrides)
+    }
+}
+
+impl Config {
+    // sets the app defaults from the config
+    pub fn apply(self, app: &mut App) {
+        if let Some(interval) = self.interval {
+            app.interval = interval;
+        }
+        if let Some(min_memory) = self.min_memory {
+            app.min_memory = min_memory;
+        }
+        if let Some(top) = self.top {
+            app.top_n = top;
+            app.top_mode = true;
+        }
+        if let Some(sort) = self.sort {
+            app.sort_column = sort;
+        }
+        if let Some(ascending) = self.ascending {
+            app.sort_descending = !ascending;
+        }
+        if let Some(len) = self.history {
+            app.history = History::new(len);
+        }
+        if let Some(columns) = self.columns {
+            // without a sort setting the default column may be hidden
+            if !columns.contains(&app.sort_column) {
+                app.sort_column = columns[0];
+            }
+            app.columns = columns;
+        }
+        app.hidden_panels.extend(self.hidden_panels);
+        if let Some(hide) = self.hide_virtual_networks {
+            app.hide_virtual_networks = hide;
+        }
+        if let Some(filesystems) = self.hidden_filesystems {
+            app.disks.hidden_filesystems = filesystems;
+        }
+        if let Some(view) = self.disk_view {
+            app.disk_view = view;
+        }
+        app.theme = self.theme;
+        app.keys = self.keys;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::Action;
+    use crossterm::event::KeyCode;
+
+    fn parse(text: &str) -> Result<Config, String> {
+        let file: ConfigFile = toml::from_str(text).map_err(|err| err.to_string())?;
+        file.parse()
+    }
+
+    #[test]
+    fn empty_config_keeps_defaults() {
+        let mut app = App::new();
+        parse("").unwrap().apply(&mut app);
+        assert_eq!(app.interval, Duration::from_secs(1));
+        assert_eq!(app.columns, SortColumn::ALL.to_vec());
+        assert_eq!(app.keys.action(KeyCode::Char('q')), Some(Action::Quit));
+    }
+
+    #[test]
+    fn applies_settings() {
+        let mut app = App::new();
+        let config = parse(
+            r#"
+            interval = 500
+            sort = "cpu"
+            columns = ["pid", "name", "cpu"]
+
+            [thresholds]
+            warning = 60
+
+            [keys]
+            quit = "Q"
+            details = ["Enter", "d"]
+            "#,
+        )
+        .unwrap();
+        config.apply(&mut app);
+        assert_eq!(app.interval, Duration::from_millis(500));
+        assert_eq!(app.sort_column, SortColumn::Cpu);
+        assert_eq!(
+            app.columns,
+            vec![SortColumn::Pid, SortColumn::Name, SortColumn::Cpu]
+        );
+        assert!((app.theme.warning_threshold - 60.0).abs() < f32::EPSILON);
+        assert_eq!(app.keys.action(KeyCode::Char('Q')), Some(Action::Quit));
+        assert_eq!(app.keys.action(KeyCode::Char('q')), None);
+        assert_eq!(app.keys.action(KeyCode::Char('d')), Some(Action::Details));
+    }
+
+    #[test]
+    fn sorts_by_a_shown_column() {
+        let mut app = App::new();
+        parse("columns = [\"pid\", \"cpu\"]")
+            .unwrap()
+            .apply(&mut app);
+        assert_eq!(app.sort_column, SortColumn::Pid);
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert!(parse("interval = 0").is_err());
+        assert!(parse("interval = 100000").is_err());
+        assert!(parse("refresh = 100").is_err());
+        assert!(parse("columns = []").is_err());
+        assert!(parse("columns = [\"pid\", \"pid\"]").is_err());
+        assert!(parse("[colors]\nok = \"purple\"").is_err());
+        assert!(parse("[thresholds]\nwarning = 90\ncritical = 80").is_err());
+        // "k" is already bound to up
+        assert!(parse("[keys]\nquit = \"k\"").is_err());
+        assert!(parse("[keys]\nexplode = \"e\"").is_err());
+        assert!(parse("hidden_panels = [\"gpu\"]").is_err());
+        assert!(parse("disk_view = \"tiny\"").is_err());
+        assert!(parse("sort = \"user\"\ncolumns = [\"pid\", \"name\"]").is_err());
+    }
+}
@@ -0,0 +1,320 @@
+use crate::processes::ProcessInfo;
+use std::ffi::OsString;
+use std::path::Path;
+use std::time::Duration;
+use sysinfo::{Pid, Process, System};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+// 90061 -> "1d 01:01:01"
+pub fn format_duration(secs: u64) -> String {
+    let days = secs / 86_400;
+    let hours = (secs % 86_400) / 3_600;
+    let minutes = (secs % 3_600) / 60;
+    let seconds = secs % 60;
+    if days > 0 {
+        format!("{days}d {hours:02}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{hours:02}:{minutes:02}:{seconds:02}")
+    }
+}
+
+// 250ms, 1.5s, 30s, then like format_duration
+pub fn format_interval(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if millis < 1_000 {
+        format!("{millis}ms")
+    } else if millis < 10_000 && !millis.is_multiple_of(1_000) {
+        format!("{:.1}s", duration.as_secs_f32())
+    } else if millis < 100_000 {
+        format!("{}s", duration.as_secs())
+    } else {
+        format_duration(duration.as_secs())
+    }
+}
+
+// sample ages are shown in steps that grow with the age, so that a long
+// interval or a pause only needs a redraw once in a while. returns the age
+// rounded down to its step and how long until that changes
+pub fn rounded_age(age: Duration) -> (Duration, Duration) {
+    let secs = age.as_secs();
+    let step = match secs {
+        0..10 => 1,
+        10..60 => 10,
+        60..3_600 => 60,
+        _ => 3_600,
+    };
+    let rounded = Duration::from_secs(secs / step * step);
+    (
+        rounded,
+        (rounded + Duration::from_secs(step)).saturating_sub(age),
+    )
+}
+
+// seconds since the unix epoch -> "2025-10-17 04:38:52 UTC"
+pub fn format_timestamp(secs: u64) -> String {
+    // days to a civil date, from Howard Hinnant's date algorithms
+    let days = secs / 86_400 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // months counted from march, so the leap day comes last
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!(
+        "{year}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        secs % 86_400 / 3_600,
+        secs % 3_600 / 60,
+        secs % 60
+    )
+}
+
+#[allow(clippy::cast_precision_loss)]
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes >= 1_000_000_000 {
+        format!("{:.2} GB", bytes as f64 / 1_000_000_000.0)
+    } else if bytes >= 1_000_000 {
+        format!("{:.2} MB", bytes as f64 / 1_000_000.0)
+    } else if bytes >= 1_000 {
+        format!("{:.2} KB", bytes as f64 / 1_000.0)
+    } else {
+        format!("{bytes} B")
+    }
+}
+
+fn format_path(path: Option<&Path>) -> String {
+    path.map_or_else(|| "-".to_string(), |path| path.display().to_string())
+}
+
+fn join_args(args: &[OsString]) -> String {
+    if args.is_empty() {
+        return "-".to_string();
+    }
+    args.iter()
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn label_value(label: &str, value: String) -> Spans<'static> {
+    Spans::from(vec![
+        Span::styled(format!("{label:<14}"), Style::default().fg(Color::Yellow)),
+        Span::raw(value),
+    ])
+}
+
+fn section(title: &str) -> Spans<'static> {
+    Spans::from(Span::styled(
+        title.to_string(),
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    ))
+}
+
+// `sample` is the process from the latest snapshot, its cpu usage is measured
+// over the whole interval unlike the one refreshed just for this view
+fn process_lines(
+    sys: &System,
+    process: &Process,
+    sample: Option<&ProcessInfo>,
+) -> Vec<Spans<'static>> {
+    let cpu_usage = sample.map_or_else(|| process.cpu_usage(), |sample| sample.cpu_usage);
+    let disk = process.disk_usage();
+    let uid_gid = format!(
+        "{}/{}",
+        process
+            .effective_user_id()
+            .map_or_else(|| "-".to_string(), |uid| uid.to_string()),
+        process
+            .effective_group_id()
+            .map_or_else(|| "-".to_string(), |gid| gid.to_string())
+    );
+
+    let mut lines = vec![
+        label_value("Name:", process.name().to_string_lossy().to_string()),
+        label_value("Command:", join_args(process.cmd())),
+        label_value("Executable:", format_path(process.exe())),
+        label_value("Cwd:", format_path(process.cwd())),
+        label_value("Root:", format_path(process.root())),
+        label_value("Status:", process.status().to_string()),
+        label_value(
+            "Started:",
+            format!(
+                "{} (up {})",
+                format_timestamp(process.start_time()),
+                format_duration(process.run_time())
+            ),
+        ),
+        label_value(
+            "Parent PID:",
+            process
+                .parent()
+                .map_or_else(|| "-".to_string(), |pid| pid.to_string()),
+        ),
+        label_value(
+            "Session:",
+            process
+                .session_id()
+                .map_or_else(|| "-".to_string(), |pid| pid.to_string()),
+        ),
+        label_value("EUID/EGID:", uid_gid),
+        label_value("CPU:", format!("{cpu_usage:.2}%")),
+        label_value("Memory:", format_bytes(process.memory())),
+        label_value("Virtual mem:", format_bytes(process.virtual_memory())),
+        label_value(
+            "Disk read:",
+            format!(
+                "{} total, {} since last refresh",
+                format_bytes(disk.total_read_bytes),
+                format_bytes(disk.read_bytes)
+            ),
+        ),
+        label_value(
+            "Disk written:",
+            format!(
+                "{} total, {} since last refresh",
+                format_bytes(disk.total_written_bytes),
+                format_bytes(disk.written_bytes)
+            ),
+        ),
+        Spans::from(""),
+    ];
+
+    // threads, with their names when sysinfo knows them
+    let mut tasks: Vec<Pid> = process
+        .tasks()
+        .map(|tasks| tasks.iter().copied().collect())
+        .unwrap_or_default();
+    tasks.sort();
+    lines.push(section(&format!("Tasks ({})", tasks.len())));
+    for tid in tasks {
+        let name = sys
+            .process(tid)
+            .map(|task| task.name().to_string_lossy().to_string())
+            .unwrap_or_default();
+        lines.push(Spans::from(format!("  {tid:<8} {name}")));
+    }
+    lines.push(Spans::from(""));
+
+    lines.push(section(&format!(
+        "Environment ({})",
+        process.environ().len()
+    )));
+    for var in process.environ() {
+        lines.push(Spans::from(format!("  {}", var.to_string_lossy())));
+    }
+    lines
+}
+
+// splits lines longer than `width` into several rows, so scrolling knows how
+// many rows there are
+fn wrap(lines: Vec<Spans<'static>>, width: usize) -> Vec<Spans<'static>> {
+    let width = width.max(1);
+    let mut rows = Vec::new();
+    for line in lines {
+        let mut row = Vec::new();
+        let mut used = 0;
+        for span in line.0 {
+            let chars: Vec<char> = span.content.chars().collect();
+            let mut rest = chars.as_slice();
+            while !rest.is_empty() {
+                if used == width {
+                    rows.push(Spans::from(std::mem::take(&mut row)));
+                    used = 0;
+                }
+                let (part, remaining) = rest.split_at((width - used).min(rest.len()));
+                row.push(Span::styled(part.iter().collect::<String>(), span.style));
+                used += part.len();
+                rest = remaining;
+            }
+        }
+        rows.push(Spans::from(row));
+    }
+    rows
+}
+
+// rows the detail view has at `width` columns, used to limit scrolling
+pub fn line_count(sys: &System, pid: Pid, width: u16) -> usize {
+    sys.process(pid).map_or(1, |process| {
+        wrap(process_lines(sys, process, None), usize::from(width)).len()
+    })
+}
+
+// columns inside the border of the detail view drawn over `area`
+pub fn text_width(area: Rect) -> u16 {
+    area.width.saturating_sub(2)
+}
+
+pub fn render_process_details<B: Backend>(
+    f: &mut Frame<B>,
+    sys: &System,
+    pid: Pid,
+    sample: Option<&ProcessInfo>,
+    scroll: u16,
+    area: Rect,
+) {
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title(format!("Process {pid} (Esc to close, Up/Down to scroll)"))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+
+    let lines = match sys.process(pid) {
+        Some(process) => wrap(
+            process_lines(sys, process, sample),
+            usize::from(text_width(area)),
+        ),
+        None => vec![Spans::from(Span::styled(
+            "Process has exited",
+            Style::default().fg(Color::LightRed),
+        ))],
+    };
+    let paragraph = Paragraph::new(lines).block(block).scroll((scroll, 0));
+    f.render_widget(paragraph, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_timestamps() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(format_timestamp(1_760_675_932), "2025-10-17 04:38:52 UTC");
+    }
+
+    #[test]
+    fn rounds_ages_in_growing_steps() {
+        let (secs, millis) = (Duration::from_secs, Duration::from_millis);
+        assert_eq!(rounded_age(millis(2_500)), (secs(2), millis(500)));
+        assert_eq!(rounded_age(secs(37)), (secs(30), secs(3)));
+        assert_eq!(rounded_age(secs(9)), (secs(9), secs(1)));
+        assert_eq!(rounded_age(secs(4_000)), (secs(3_600), secs(3_200)));
+    }
+
+    #[test]
+    fn wraps_long_lines() {
+        let lines = vec![label_value("Command:", "x".repeat(20)), Spans::from("")];
+        let rows = wrap(lines, 10);
+        // 14 columns of label and 20 of value
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[3].width(), 4);
+        assert_eq!(rows[4].width(), 0);
+    }
+}
@@ -0,0 +1,429 @@
+use crate::collector::DiskSample;
+use crate::details::format_bytes;
+use crate::stats::render_label_value;
+use crate::theme::Theme;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    style::{Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    Frame,
+};
+
+// how long mounted and unmounted disks stay highlighted
+const CHANGE_HIGHLIGHT: Duration = Duration::from_secs(5);
+// per disk in the detailed view: five lines, their margin and the border
+const DISK_ROWS: u16 = 9;
+
+// a block of details per disk, or a table with a line per disk
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiskView {
+    Detailed,
+    Compact,
+}
+
+impl DiskView {
+    pub fn next(self) -> Self {
+        match self {
+            DiskView::Detailed => DiskView::Compact,
+            DiskView::Compact => DiskView::Detailed,
+        }
+    }
+}
+
+impl FromStr for DiskView {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "detailed" => Ok(DiskView::Detailed),
+            "compact" => Ok(DiskView::Compact),
+            _ => Err(format!(
+                "unknown disk view {s:?}, expected detailed or compact"
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiskChange {
+    Added,
+    Removed,
+}
+
+pub struct DiskEntry<'a> {
+    pub disk: &'a DiskSample,
+    // set for a few seconds after the disk was mounted or unmounted
+    pub change: Option<DiskChange>,
+}
+
+// the disks of the disk panel. follows the mounts of each snapshot, leaving
+// out the hidden file systems, and remembers what changed so new and
+// unmounted disks can be highlighted for a while
+#[derive(Default)]
+pub struct DiskList {
+    // file system names, e.g. "tmpfs", "overlay" or "squashfs"
+    pub hidden_filesystems: Vec<String>,
+    disks: Vec<DiskSample>,
+    // by mount point, when the disk showed up. the disks of the first
+    // snapshot are not new
+    added: HashMap<String, Instant>,
+    // unmounted disks with their last sample
+    removed: Vec<(DiskSample, Instant)>,
+    started: bool,
+}
+
+impl DiskList {
+    pub fn update(&mut self, disks: &[DiskSample], now: Instant) {
+        let disks = disks
+            .iter()
+            .filter(|disk| !self.hidden_filesystems.contains(&disk.file_system))
+            .cloned()
+            .collect();
+        let previous = std::mem::replace(&mut self.disks, disks);
+        if self.started {
+            for disk in &self.disks {
+                if !previous.iter().any(|p| p.mount_point == disk.mount_point) {
+                    self.added.insert(disk.mount_point.clone(), now);
+                    self.removed
+                        .retain(|(r, _)| r.mount_point != disk.mount_point);
+                }
+            }
+            for disk in previous {
+                if !self.disks.iter().any(|d| d.mount_point == disk.mount_point) {
+                    self.added.remove(&disk.mount_point);
+                    self.removed.push((disk, now));
+                }
+            }
+        }
+        self.started = true;
+
+        let recent = |since: &Instant| now.saturating_duration_since(*since) < CHANGE_HIGHLIGHT;
+        self.added.retain(|_, since| recent(since));
+        self.removed.retain(|(_, since)| recent(since));
+    }
+
+    // the mounted disks followed by the recently unmounted ones
+    pub fn entries(&self, now: Instant) -> Vec<DiskEntry<'_>> {
+        let recent = |since: &Instant| now.saturating_duration_since(*since) < CHANGE_HIGHLIGHT;
+        let mounted = self.disks.iter().map(|disk| DiskEntry {
+            disk,
+            change: self
+                .added
+                .get(&disk.mount_point)
+                .filter(|since| recent(since))
+                .map(|_| DiskChange::Added),
+        });
+        let unmounted = self
+            .removed
+            .iter()
+            .filter(|(_, since)| recent(since))
+            .map(|(disk, _)| DiskEntry {
+                disk,
+                change: Some(DiskChange::Removed),
+            });
+        mounted.chain(unmounted).collect()
+    }
+}
+
+// rows the panel needs to show `count` disks: the border and, in the compact
+// view, the table header. without disks a line says so
+pub fn panel_rows(view: DiskView, count: u16) -> u16 {
+    match view {
+        _ if count == 0 => 3,
+        DiskView::Detailed => 2u16.saturating_add(count.saturating_mul(DISK_ROWS)),
+        DiskView::Compact => 3u16.saturating_add(count),
+    }
+}
+
+// share of the space in use, pseudo file systems have no space at all
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn used_percent(disk: &DiskSample) -> f32 {
+    if disk.total_space == 0 {
+        return 0.0;
+    }
+    let used = disk.total_space.saturating_sub(disk.available_space);
+    (used as f64 / disk.total_space as f64 * 100.0) as f32
+}
+
+// "██████░░░░" for 60% in 10 columns
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn usage_bar(percent: f32, width: usize) -> String {
+    let filled = ((percent.clamp(0.0, 100.0) / 100.0 * width as f32).round() as usize).min(width);
+    "█".repeat(filled) + &"░".repeat(width - filled)
+}
+
+// index of the first disk shown, scrolling stops once the last disk is in
+// view
+fn first_shown(scroll: usize, count: usize, fit: usize) -> usize {
+    scroll.min(count.saturating_sub(fit))
+}
+
+// just mounted and unmounted disks stand out for a few seconds
+fn change_style(change: Option<DiskChange>, theme: &Theme) -> Style {
+    match change {
+        Some(DiskChange::Added) => Style::default().fg(theme.ok).add_modifier(Modifier::BOLD),
+        Some(DiskChange::Removed) => Style::default().fg(theme.critical),
+        None => Style::default(),
+    }
+}
+
+fn render_disk<B: Backend>(
+    f: &mut Frame<B>,
+    index: usize,
+    entry: &DiskEntry,
+    theme: &Theme,
+    chunk: Rect,
+) {
+    let disk = entry.disk;
+    let style = change_style(entry.change, theme);
+    let title = match entry.change {
+        Some(DiskChange::Added) => format!("Disk {index} (new)"),
+        Some(DiskChange::Removed) => format!("Disk {index} (unmounted)"),
+        None => format!("Disk {index}"),
+    };
+    let outer_block = Block::default()
+        .title(Span::styled(title, style))
+        .borders(Borders::ALL)
+        .border_style(style);
+    f.render_widget(outer_block, chunk);
+
+    let disk_chunk = Layout::default()
+        .direction(Direction::Horizontal)
+        .horizontal_margin(1)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunk);
+    let lines = |chunk: Rect| {
+        Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([Constraint::Length(1); 5])
+            .split(chunk)
+    };
+    let disk_label_chunks = lines(disk_chunk[0]);
+    let disk_value_chunks = lines(disk_chunk[1]);
+
+    render_label_value(
+        f,
+        "Mount Point:",
+        disk.mount_point.clone(),
+        disk_label_chunks[0],
+        disk_value_chunks[0],
+    );
+    render_label_value(
+        f,
+        "Name: ",
+        disk.name.clone(),
+        disk_label_chunks[1],
+        disk_value_chunks[1],
+    );
+
+    let percentage_used = used_percent(disk);
+    f.render_widget(Paragraph::new("Usage: "), disk_label_chunks[2]);
+    let percent = Span::styled(
+        format!("{percentage_used:.2}%"),
+        Style::default().fg(theme.severity(percentage_used)),
+    );
+    f.render_widget(
+        Paragraph::new(percent).alignment(Alignment::Right),
+        disk_value_chunks[2],
+    );
+
+    render_label_value(
+        f,
+        "Filesystem: ",
+        disk.file_system.clone(),
+        disk_label_chunks[3],
+        disk_value_chunks[3],
+    );
+    render_label_value(
+        f,
+        "Kind: ",
+        disk.kind.clone(),
+        disk_label_chunks[4],
+        disk_value_chunks[4],
+    );
+}
+
+// a line per disk with its size and a bar of the space in use
+fn render_disk_table<B: Backend>(
+    f: &mut Frame<B>,
+    disks: &[DiskEntry],
+    theme: &Theme,
+    block: Block,
+    chunk: Rect,
+) {
+    // " 60% ██████░░░░" in the last column, the percentage first so a bar
+    // that is a little too long only loses its end
+    let usage_width = usize::from(chunk.width.saturating_sub(5) * 36 / 100).saturating_sub(6);
+    let header_style = Style::default()
+        .fg(theme.header)
+        .add_modifier(Modifier::BOLD);
+    let header = Row::new(["Mount", "Filesystem", "Size", "Usage"]).style(header_style);
+    let rows = disks.iter().map(|entry| {
+        let percent = used_percent(entry.disk);
+        let usage = Span::styled(
+            format!("{percent:>3.0}% {}", usage_bar(percent, usage_width)),
+            Style::default().fg(theme.severity(percent)),
+        );
+        Row::new([
+            Cell::from(entry.disk.mount_point.clone()),
+            Cell::from(entry.disk.file_system.clone()),
+            Cell::from(format_bytes(entry.disk.total_space)),
+            Cell::from(usage),
+        ])
+        .style(change_style(entry.change, theme))
+    });
+    let table = Table::new(rows).header(header).block(block).widths(&[
+        Constraint::Percentage(30),
+        Constraint::Percentage(17),
+        Constraint::Percentage(17),
+        Constraint::Percentage(36),
+    ]);
+    f.render_widget(table, chunk);
+}
+
+// the disks from `scroll` on, as many as fit in `chunk`. the title tells which
+// ones are shown when they do not all fit. returns the first disk shown
+pub fn render_disks<B: Backend>(
+    f: &mut Frame<B>,
+    disks: &[DiskEntry],
+    view: DiskView,
+    scroll: usize,
+    theme: &Theme,
+    chunk: Rect,
+) -> usize {
+    let padding_chunk = Layout::default()
+        .direction(Direction::Horizontal)
+        .horizontal_margin(1)
+        .constraints([Constraint::Min(1)].as_ref())
+        .split(chunk)[0];
+    // a column of padding inside the border, like the other panels
+    let inner = padding_chunk.inner(&Margin {
+        vertical: 1,
+        horizontal: 2,
+    });
+    // too short for even one disk's details, the table at least lists some
+    let view = if view == DiskView::Detailed && inner.height < DISK_ROWS {
+        DiskView::Compact
+    } else {
+        view
+    };
+    let fit = match view {
+        DiskView::Detailed => usize::from(inner.height / DISK_ROWS),
+        // the header takes a line
+        DiskView::Compact => usize::from(inner.height.saturating_sub(1)),
+    }
+    .min(disks.len());
+    let first = first_shown(scroll, disks.len(), fit);
+    let title = if fit > 0 && fit < disks.len() {
+        format!("Disks ({}-{} of {})", first + 1, first + fit, disks.len())
+    } else {
+        "Disks".to_string()
+    };
+    let block = Block::default().title(title).borders(Borders::ALL);
+
+    if disks.is_empty() {
+        f.render_widget(
+            Paragraph::new(" No disks detected").block(block),
+            padding_chunk,
+        );
+        return first;
+    }
+
+    let shown = &disks[first..first + fit];
+    match view {
+        DiskView::Detailed => {
+            f.render_widget(block, padding_chunk);
+            let mut constraints = vec![Constraint::Length(DISK_ROWS); shown.len()];
+            constraints.push(Constraint::Min(0));
+            let disk_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(constraints)
+                .split(inner);
+            for (i, (entry, chunk)) in shown.iter().zip(disk_chunks.iter()).enumerate() {
+                render_disk(f, first + i, entry, theme, *chunk);
+            }
+        }
+        DiskView::Compact => render_disk_table(f, shown, theme, block, padding_chunk),
+    }
+    first
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn disk(mount_point: &str, file_system: &str) -> DiskSample {
+        DiskSample {
+            name: "sda1".to_string(),
+            mount_point: mount_point.to_string(),
+            file_system: file_system.to_string(),
+            kind: "SSD".to_string(),
+            total_space: 100,
+            available_space: 50,
+        }
+    }
+
+    fn changes(list: &DiskList, now: Instant) -> Vec<(String, Option<DiskChange>)> {
+        list.entries(now)
+            .iter()
+            .map(|entry| (entry.disk.mount_point.clone(), entry.change))
+            .collect()
+    }
+
+    #[test]
+    fn highlights_mounts_and_unmounts() {
+        let start = Instant::now();
+        let mut list = DiskList {
+            hidden_filesystems: vec!["tmpfs".to_string()],
+            ..DiskList::default()
+        };
+        list.update(&[disk("/", "ext4"), disk("/run", "tmpfs")], start);
+        assert_eq!(changes(&list, start), [("/".to_string(), None)]);
+
+        let later = start + Duration::from_secs(1);
+        list.update(&[disk("/media/usb", "vfat")], later);
+        assert_eq!(
+            changes(&list, later),
+            [
+                ("/media/usb".to_string(), Some(DiskChange::Added)),
+                ("/".to_string(), Some(DiskChange::Removed)),
+            ]
+        );
+
+        // the highlight ends even without a new snapshot
+        let much_later = later + CHANGE_HIGHLIGHT;
+        assert_eq!(
+            changes(&list, much_later),
+            [("/media/usb".to_string(), None)]
+        );
+    }
+
+    #[test]
+    fn usage_bars() {
+        assert!((used_percent(&disk("/", "ext4")) - 50.0).abs() < f32::EPSILON);
+        let mut empty = disk("/dev/pts", "devpts");
+        empty.total_space = 0;
+        assert!(used_percent(&empty).abs() < f32::EPSILON);
+        assert_eq!(usage_bar(60.0, 10), "██████░░░░");
+        assert_eq!(usage_bar(120.0, 4), "████");
+        assert_eq!(usage_bar(50.0, 0), "");
+    }
+
+    #[test]
+    fn scrolling_stops_at_the_last_disk() {
+        assert_eq!(first_shown(0, 12, 5), 0);
+        assert_eq!(first_shown(4, 12, 5), 4);
+        assert_eq!(first_shown(10, 12, 5), 7);
+        assert_eq!(first_shown(3, 2, 5), 0);
+    }
+}
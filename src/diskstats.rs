@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+// /proc/diskstats counts in 512 byte sectors regardless of the device
+const SECTOR_SIZE: u64 = 512;
+
+// cumulative counters of a block device
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+struct Counters {
+    reads: u64,
+    sectors_read: u64,
+    writes: u64,
+    sectors_written: u64,
+    // time the device had requests in flight
+    busy_ms: u64,
+}
+
+// activity of a block device between two samples
+pub struct DiskIoSample {
+    pub name: String,
+    // bytes per second
+    pub read_rate: u64,
+    pub write_rate: u64,
+    // reads and writes completed per second
+    pub iops: u64,
+    // share of the time the device was busy, in percent
+    pub utilization: f32,
+}
+
+// "   8       0 sda 1520 33 ..." -> ("sda", counters), lines that are too
+// short are skipped
+fn parse(text: &str) -> Vec<(String, Counters)> {
+    text.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let number = |i: usize| fields.get(i)?.parse::<u64>().ok();
+            let counters = Counters {
+                reads: number(3)?,
+                sectors_read: number(5)?,
+                writes: number(7)?,
+                sectors_written: number(9)?,
+                busy_ms: number(12)?,
+            };
+            Some((fields[2].to_string(), counters))
+        })
+        .collect()
+}
+
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn sample(name: &str, previous: Counters, current: Counters, elapsed: Duration) -> DiskIoSample {
+    let seconds = elapsed.as_secs_f64();
+    // counters wrap or restart when a device is re-added
+    let per_second = |previous: u64, current: u64| {
+        if seconds > 0.0 {
+            (current.saturating_sub(previous) as f64 / seconds) as u64
+        } else {
+            0
+        }
+    };
+    let busy_ms = current.busy_ms.saturating_sub(previous.busy_ms);
+    let utilization = if seconds > 0.0 {
+        (busy_ms as f64 / (seconds * 1_000.0) * 100.0).min(100.0) as f32
+    } else {
+        0.0
+    };
+    DiskIoSample {
+        name: name.to_string(),
+        read_rate: per_second(previous.sectors_read, current.sectors_read) * SECTOR_SIZE,
+        write_rate: per_second(previous.sectors_written, current.sectors_written) * SECTOR_SIZE,
+        iops: per_second(
+            previous.reads + previous.writes,
+            current.reads + current.writes,
+        ),
+        utilization,
+    }
+}
+
+// whole devices have a directory in /sys/block, partitions are only listed
+// below their device
+fn is_whole_device(name: &str) -> bool {
+    Path::new("/sys/block").join(name).exists()
+}
+
+// turns the cumulative counters of /proc/diskstats into rates, which needs
+// the counters of the previous sample. only linux has the file, elsewhere
+// there are no devices
+pub struct DiskStats {
+    previous: HashMap<String, Counters>,
+}
+
+impl DiskStats {
+    pub fn new() -> Self {
+        DiskStats {
+            previous: read().into_iter().collect(),
+        }
+    }
+
+    // activity of every whole device since the previous call, devices that
+    // never did any i/o (e.g. unused loop devices) are left out
+    pub fn sample(&mut self, elapsed: Duration) -> Vec<DiskIoSample> {
+        let current = read();
+        let samples = current
+            .iter()
+            .filter(|(name, counters)| *counters != Counters::default() && is_whole_device(name))
+            .map(|(name, counters)| {
+                let previous = self.previous.get(name).copied().unwrap_or(*counters);
+                sample(name, previous, *counters, elapsed)
+            })
+            .collect();
+        self.previous = current.into_iter().collect();
+        samples
+    }
+}
+
+fn read() -> Vec<(String, Counters)> {
+    fs::read_to_string("/proc/diskstats")
+        .map(|text| parse(&text))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_counters() {
+        let text = "\
+ 252       0 vda 9538 2651 1104322 4216 6153 4687 397418 9404 0 10236 14404 0 0 0 0
+ 252       1 vda1 9400 2651 1100000 4200 6153 4687 397418 9404 0 10200 14300
+   7       0 loop0
+";
+        let devices = parse(text);
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].0, "vda");
+        assert_eq!(
+            devices[0].1,
+            Counters {
+                reads: 9538,
+                sectors_read: 1_104_322,
+                writes: 6153,
+                sectors_written: 397_418,
+                busy_ms: 10236,
+            }
+        );
+    }
+
+    #[test]
+    fn rates_between_samples() {
+        let previous = Counters::default();
+        let current = Counters {
+            reads: 100,
+            sectors_read: 2_000,
+            writes: 50,
+            sectors_written: 4_000,
+            busy_ms: 750,
+        };
+        let sample = sample("sda", previous, current, Duration::from_secs(2));
+        assert_eq!(sample.read_rate, 512_000);
+        assert_eq!(sample.write_rate, 1_024_000);
+        assert_eq!(sample.iops, 75);
+        assert!((sample.utilization - 37.5).abs() < f32::EPSILON);
+    }
+}
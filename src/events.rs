@@ -0,0 +1,33 @@
+use crate::collector::Snapshot;
+use crossterm::event::{self, Event};
+use std::io;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+// everything the main loop reacts to, sent over a single channel so the loop
+// can sleep until one of them happens
+pub enum AppEvent {
+    Snapshot(Box<Snapshot>),
+    // the collector thread returned or panicked
+    CollectorStopped,
+    Terminal(Event),
+    InputError(io::Error),
+    Exit,
+}
+
+// reads key presses, resizes and other terminal events on their own thread,
+// the thread stops once the receiver is dropped
+pub fn spawn_input(sender: Sender<AppEvent>) {
+    thread::spawn(move || loop {
+        let event = match event::read() {
+            Ok(event) => AppEvent::Terminal(event),
+            Err(err) => {
+                let _ = sender.send(AppEvent::InputError(err));
+                return;
+            }
+        };
+        if sender.send(event).is_err() {
+            return;
+        }
+    });
+}
@@ -0,0 +1,105 @@
+use crate::processes::ProcessInfo;
+use crate::users::UserCache;
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+
+// narrows the process table, parsed from the text typed after `/`
+//   rustc        name contains "rustc" (case insensitive)
+//   1234         pid is 1234, or name contains "1234"
+//   pid:1234     pid is 1234
+//   user:alice   effective user is alice (a numeric uid works too)
+//   re:^cargo    name matches the regex (case insensitive)
+pub enum ProcessFilter {
+    Name(String),
+    Regex(Regex),
+    Pid(u32),
+    User(String),
+}
+
+impl ProcessFilter {
+    // returns Ok(None) for an empty filter, Err for an invalid one
+    pub fn parse(input: &str) -> Result<Option<ProcessFilter>, String> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Ok(None);
+        }
+
+        let filter = if let Some(pid) = input.strip_prefix("pid:") {
+            let pid = pid
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid pid: {pid}"))?;
+            ProcessFilter::Pid(pid)
+        } else if let Some(user) = input.strip_prefix("user:") {
+            ProcessFilter::User(user.trim().to_string())
+        } else if let Some(pattern) = input.strip_prefix("re:") {
+            let regex = RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|err| format!("Invalid regex: {err}"))?;
+            ProcessFilter::Regex(regex)
+        } else {
+            ProcessFilter::Name(input.to_lowercase())
+        };
+        Ok(Some(filter))
+    }
+
+    pub fn matches(&self, process: &ProcessInfo, users: &UserCache) -> bool {
+        match self {
+            ProcessFilter::Pid(pid) => process.pid.as_u32() == *pid,
+            ProcessFilter::User(user) => process
+                .user_id
+                .is_some_and(|uid| uid.to_string() == *user || users.user_name(uid) == *user),
+            ProcessFilter::Name(text) => {
+                // a bare number is most likely a pid, but could be part of a name
+                text.parse::<u32>()
+                    .is_ok_and(|pid| process.pid.as_u32() == pid)
+                    || self.name_match(&process.name).is_some()
+            }
+            ProcessFilter::Regex(_) => self.name_match(&process.name).is_some(),
+        }
+    }
+
+    // byte range of the part of `name` that matched, used for highlighting
+    pub fn name_match(&self, name: &str) -> Option<Range<usize>> {
+        match self {
+            ProcessFilter::Name(text) => {
+                // lowercasing can change byte lengths, so remember where in `name`
+                // each byte of the lowercased name came from
+                let mut lower = String::with_capacity(name.len());
+                let mut origin = Vec::with_capacity(name.len());
+                for (i, c) in name.char_indices() {
+                    for lc in c.to_lowercase() {
+                        lower.push(lc);
+                        origin.extend(std::iter::repeat_n(i..i + c.len_utf8(), lc.len_utf8()));
+                    }
+                }
+                let start = lower.find(text.as_str())?;
+                let end = start + text.len();
+                if start == end {
+                    return Some(0..0);
+                }
+                Some(origin[start].start..origin[end - 1].end)
+            }
+            ProcessFilter::Regex(regex) => regex.find(name).map(|m| m.range()),
+            ProcessFilter::Pid(_) | ProcessFilter::User(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_match_maps_back_to_the_original_name() {
+        let filter = ProcessFilter::parse("ß").unwrap().unwrap();
+        // "İ" grows and "ẞ" shrinks when lowercased
+        let name = "İẞ";
+        let range = filter.name_match(name).unwrap();
+        assert_eq!(&name[range], "ẞ");
+
+        let filter = ProcessFilter::parse("CARGO").unwrap().unwrap();
+        assert_eq!(filter.name_match("my-Cargo"), Some(3..8));
+    }
+}
@@ -0,0 +1,120 @@
+use crate::collector::Snapshot;
+use std::collections::{HashMap, VecDeque};
+
+pub const DEFAULT_HISTORY: usize = 60;
+pub const MIN_HISTORY: usize = 2;
+pub const MAX_HISTORY: usize = 3_600;
+
+// the last `len` samples of everything drawn as a graph, oldest first
+pub struct History {
+    len: usize,
+    pub global_cpu: VecDeque<f32>,
+    pub cores: Vec<VecDeque<f32>>,
+    // percentages of the total memory and swap
+    pub used_memory: VecDeque<f32>,
+    pub available_memory: VecDeque<f32>,
+    pub used_swap: VecDeque<f32>,
+    // by interface name, interfaces that went away are dropped
+    pub networks: HashMap<String, NetworkHistory>,
+}
+
+// bytes per second
+#[derive(Default)]
+pub struct NetworkHistory {
+    pub received: VecDeque<u64>,
+    pub transmitted: VecDeque<u64>,
+}
+
+impl History {
+    pub fn new(len: usize) -> Self {
+        History {
+            len,
+            global_cpu: VecDeque::with_capacity(len),
+            cores: Vec::new(),
+            used_memory: VecDeque::with_capacity(len),
+            available_memory: VecDeque::with_capacity(len),
+            used_swap: VecDeque::with_capacity(len),
+            networks: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn push(&mut self, snapshot: &Snapshot) {
+        push_value(&mut self.global_cpu, snapshot.global_cpu_usage, self.len);
+        // the number of cores only changes if cpus are hotplugged
+        self.cores
+            .resize_with(snapshot.cpus.len(), || VecDeque::with_capacity(self.len));
+        for (values, cpu) in self.cores.iter_mut().zip(&snapshot.cpus) {
+            push_value(values, cpu.usage, self.len);
+        }
+
+        let memory = |bytes| percent(bytes, snapshot.total_memory);
+        push_value(
+            &mut self.used_memory,
+            memory(snapshot.used_memory),
+            self.len,
+        );
+        push_value(
+            &mut self.available_memory,
+            memory(snapshot.available_memory),
+            self.len,
+        );
+        let swap = percent(snapshot.used_swap, snapshot.total_swap);
+        push_value(&mut self.used_swap, swap, self.len);
+
+        self.networks.retain(|name, _| {
+            snapshot
+                .networks
+                .iter()
+                .any(|network| network.name == *name)
+        });
+        for network in &snapshot.networks {
+            let history = self.networks.entry(network.name.clone()).or_default();
+            push_value(&mut history.received, network.receive_rate, self.len);
+            push_value(&mut history.transmitted, network.transmit_rate, self.len);
+        }
+    }
+}
+
+fn push_value<T>(values: &mut VecDeque<T>, value: T, len: usize) {
+    if values.len() == len {
+        values.pop_front();
+    }
+    values.push_back(value);
+}
+
+// 0 without swap instead of NaN
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn percent(part: u64, total: u64) -> f32 {
+    if total == 0 {
+        0.0
+    } else {
+        (part as f64 / total as f64 * 100.0) as f32
+    }
+}
+
+// number of samples to keep from a number given by the user
+pub fn parse_history(len: usize) -> Result<usize, String> {
+    if (MIN_HISTORY..=MAX_HISTORY).contains(&len) {
+        Ok(len)
+    } else {
+        Err(format!("must be between {MIN_HISTORY} and {MAX_HISTORY}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_oldest_when_full() {
+        let mut values = VecDeque::new();
+        for value in [1.0, 2.0, 3.0, 4.0] {
+            push_value(&mut values, value, 3);
+        }
+        assert_eq!(values, [2.0, 3.0, 4.0]);
+    }
+}
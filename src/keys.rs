@@ -0,0 +1,248 @@
+use crate::layout::Panel;
+use crossterm::event::KeyCode;
+use std::collections::HashMap;
+
+// everything a key can be bound to in the main view, dialogs and prompts use
+// fixed keys
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Details,
+    Signal,
+    SortNext,
+    SortPrev,
+    Reverse,
+    Grow,
+    Shrink,
+    TopMode,
+    Threshold,
+    Filter,
+    Tree,
+    Collapse,
+    Expand,
+    ToggleCollapse,
+    Command,
+    ScrollLeft,
+    ScrollRight,
+    Faster,
+    Slower,
+    Pause,
+    TogglePanel(Panel),
+    // the next panel key zooms that panel instead of toggling it
+    Zoom,
+    VirtualNetworks,
+    DiskView,
+    ScrollDisksUp,
+    ScrollDisksDown,
+}
+
+// config name, default keys and the label shown in the status line hint
+// (actions without a label are left out of the hint)
+const ACTIONS: [(Action, &str, &[&str], Option<&str>); 40] = [
+    (Action::Quit, "quit", &["q", "Esc"], Some("quit")),
+    (Action::Up, "up", &["Up", "k"], None),
+    (Action::Down, "down", &["Down", "j"], None),
+    (Action::PageUp, "page_up", &["PageUp"], None),
+    (Action::PageDown, "page_down", &["PageDown"], None),
+    (Action::Home, "home", &["Home"], None),
+    (Action::End, "end", &["End"], None),
+    (Action::Details, "details", &["Enter"], Some("details")),
+    (Action::Signal, "signal", &["x"], Some("signal")),
+    (Action::SortNext, "sort_next", &[">", "."], Some("sort")),
+    (Action::SortPrev, "sort_prev", &["<", ","], None),
+    (Action::Reverse, "reverse", &["r"], Some("reverse")),
+    (Action::Grow, "grow", &["+", "="], None),
+    (Action::Shrink, "shrink", &["-", "_"], None),
+    (Action::TopMode, "top", &["t"], Some("top N")),
+    (Action::Threshold, "threshold", &["m"], Some("threshold")),
+    (Action::Filter, "filter", &["/"], Some("filter")),
+    (Action::Tree, "tree", &["T"], Some("tree")),
+    (Action::Collapse, "collapse", &["Left", "h"], None),
+    (Action::Expand, "expand", &["Right", "l"], None),
+    (Action::ToggleCollapse, "toggle_collapse", &["Space"], None),
+    (Action::Command, "command", &["c"], Some("command")),
+    (Action::ScrollLeft, "scroll_left", &["["], None),
+    (Action::ScrollRight, "scroll_right", &["]"], None),
+    (Action::Faster, "faster", &["f"], None),
+    (Action::Slower, "slower", &["s"], None),
+    (Action::Pause, "pause", &["p"], Some("pause")),
+    (Action::TogglePanel(Panel::Cpu), "toggle_cpu", &["1"], None),
+    (
+        Action::TogglePanel(Panel::Memory),
+        "toggle_memory",
+        &["2"],
+        None,
+    ),
+    (
+        Action::TogglePanel(Panel::Swap),
+        "toggle_swap",
+        &["3"],
+        None,
+    ),
+    (
+        Action::TogglePanel(Panel::Disks),
+        "toggle_disks",
+        &["4"],
+        None,
+    ),
+    (
+        Action::TogglePanel(Panel::System),
+        "toggle_system",
+        &["5"],
+        None,
+    ),
+    (
+        Action::TogglePanel(Panel::Processes),
+        "toggle_processes",
+        &["6"],
+        None,
+    ),
+    (
+        Action::TogglePanel(Panel::Network),
+        "toggle_network",
+        &["7"],
+        None,
+    ),
+    (
+        Action::TogglePanel(Panel::DiskIo),
+        "toggle_disk_io",
+        &["8"],
+        None,
+    ),
+    (Action::Zoom, "zoom", &["z"], Some("zoom")),
+    (Action::VirtualNetworks, "virtual_networks", &["v"], None),
+    (Action::DiskView, "disk_view", &["D"], None),
+    (Action::ScrollDisksUp, "scroll_disks_up", &["{"], None),
+    (Action::ScrollDisksDown, "scroll_disks_down", &["}"], None),
+];
+
+// "q" -> Char('q'), "PageUp" -> PageUp, "F5" -> F(5)
+pub fn parse_key(name: &str) -> Result<KeyCode, String> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeyCode::Char(c));
+    }
+    let key = match name.to_lowercase().as_str() {
+        "esc" | "escape" => KeyCode::Esc,
+        "enter" | "return" => KeyCode::Enter,
+        "space" => KeyCode::Char(' '),
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        lower => {
+            let number = lower
+                .strip_prefix('f')
+                .and_then(|n| n.parse::<u8>().ok())
+                .filter(|n| (1..=12).contains(n));
+            match number {
+                Some(n) => KeyCode::F(n),
+                None => return Err(format!("unknown key {name:?}")),
+            }
+        }
+    };
+    Ok(key)
+}
+
+fn action_name(action: Action) -> &'static str {
+    ACTIONS
+        .iter()
+        .find(|(a, _, _, _)| *a == action)
+        .map_or("?", |(_, name, _, _)| name)
+}
+
+fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::F(n) => format!("F{n}"),
+        other => format!("{other:?}"),
+    }
+}
+
+pub struct KeyBindings {
+    keys: HashMap<KeyCode, Action>,
+    // first key of each action, for the hint
+    primary: HashMap<Action, KeyCode>,
+}
+
+impl KeyBindings {
+    // the default bindings with `overrides` (config name -> key names) applied
+    pub fn new(overrides: &HashMap<String, Vec<String>>) -> Result<Self, String> {
+        for name in overrides.keys() {
+            if !ACTIONS
+                .iter()
+                .any(|(_, action_name, _, _)| action_name == name)
+            {
+                return Err(format!("unknown action {name:?} in [keys]"));
+            }
+        }
+
+        let mut bindings = KeyBindings {
+            keys: HashMap::new(),
+            primary: HashMap::new(),
+        };
+        for (action, name, defaults, _) in ACTIONS {
+            let names: Vec<&str> = match overrides.get(name) {
+                Some(keys) => keys.iter().map(String::as_str).collect(),
+                None => defaults.to_vec(),
+            };
+            for key_name in names {
+                let key = parse_key(key_name).map_err(|err| format!("{err} for {name}"))?;
+                if let Some(old) = bindings
+                    .keys
+                    .insert(key, action)
+                    .filter(|old| *old != action)
+                {
+                    return Err(format!(
+                        "key {key_name:?} is bound to both {} and {name}",
+                        action_name(old)
+                    ));
+                }
+                bindings.primary.entry(action).or_insert(key);
+            }
+        }
+        Ok(bindings)
+    }
+
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        self.keys.get(&key).copied()
+    }
+
+    // name of the first key bound to `action`
+    pub fn key(&self, action: Action) -> Option<String> {
+        self.primary.get(&action).map(|key| key_name(*key))
+    }
+
+    // "q: quit  Enter: details  ..." for the status line
+    pub fn hint(&self) -> String {
+        ACTIONS
+            .iter()
+            .filter_map(|(action, _, _, label)| {
+                let key = self.primary.get(action)?;
+                Some(format!("{}: {}", key_name(*key), (*label)?))
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings::new(&HashMap::new()).expect("default key bindings are valid")
+    }
+}
@@ -0,0 +1,136 @@
+use std::str::FromStr;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+
+// below this the stats and the process table are stacked instead of side by
+// side
+const SIDE_BY_SIDE_WIDTH: u16 = 80;
+const MIN_STATS_WIDTH: u16 = 40;
+const MAX_STATS_WIDTH: u16 = 70;
+
+// everything that can be hidden or zoomed, in the order of their keys
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Panel {
+    Cpu,
+    Memory,
+    Swap,
+    Disks,
+    System,
+    Processes,
+    Network,
+    DiskIo,
+}
+
+impl Panel {
+    pub const ALL: [Panel; 8] = [
+        Panel::Cpu,
+        Panel::Memory,
+        Panel::Swap,
+        Panel::Disks,
+        Panel::System,
+        Panel::Processes,
+        Panel::Network,
+        Panel::DiskIo,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Panel::Cpu => "cpu",
+            Panel::Memory => "memory",
+            Panel::Swap => "swap",
+            Panel::Disks => "disks",
+            Panel::System => "system",
+            Panel::Processes => "processes",
+            Panel::Network => "network",
+            Panel::DiskIo => "disk_io",
+        }
+    }
+}
+
+impl FromStr for Panel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Panel::ALL
+            .into_iter()
+            .find(|panel| panel.name() == s.to_lowercase())
+            .ok_or_else(|| {
+                format!(
+                    "unknown panel {s:?}, expected cpu, memory, swap, disks, system, processes, network or disk_io"
+                )
+            })
+    }
+}
+
+// where the stats and the process table go, None for the hidden ones
+pub struct ScreenLayout {
+    pub stats: Option<Rect>,
+    pub processes: Option<Rect>,
+}
+
+// side by side on wide terminals with the table taking whatever the stats do
+// not need, stacked on narrow ones. either one gets the whole area when the
+// other is hidden
+pub fn split_screen(area: Rect, show_stats: bool, show_processes: bool) -> ScreenLayout {
+    if !show_stats || !show_processes {
+        return ScreenLayout {
+            stats: show_stats.then_some(area),
+            processes: show_processes.then_some(area),
+        };
+    }
+
+    let chunks = if area.width >= SIDE_BY_SIDE_WIDTH {
+        let stats_width = (area.width * 2 / 5).clamp(MIN_STATS_WIDTH, MAX_STATS_WIDTH);
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(stats_width), Constraint::Min(0)].as_ref())
+            .split(area)
+    } else {
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(area)
+    };
+    ScreenLayout {
+        stats: Some(chunks[0]),
+        processes: Some(chunks[1]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn side_by_side_when_wide() {
+        let layout = split_screen(Rect::new(0, 0, 200, 50), true, true);
+        assert_eq!(layout.stats, Some(Rect::new(0, 0, 70, 50)));
+        assert_eq!(layout.processes, Some(Rect::new(70, 0, 130, 50)));
+
+        let layout = split_screen(Rect::new(0, 0, 90, 50), true, true);
+        assert_eq!(layout.stats.map(|stats| stats.width), Some(MIN_STATS_WIDTH));
+    }
+
+    #[test]
+    fn stacked_when_narrow() {
+        let layout = split_screen(Rect::new(0, 0, 60, 50), true, true);
+        assert_eq!(layout.stats, Some(Rect::new(0, 0, 60, 25)));
+        assert_eq!(layout.processes, Some(Rect::new(0, 25, 60, 25)));
+    }
+
+    #[test]
+    fn one_pane_fills_the_screen() {
+        let area = Rect::new(1, 1, 60, 20);
+        let layout = split_screen(area, true, false);
+        assert_eq!(layout.stats, Some(area));
+        assert_eq!(layout.processes, None);
+        let layout = split_screen(area, false, true);
+        assert_eq!(layout.stats, None);
+        assert_eq!(layout.processes, Some(area));
+    }
+
+    #[test]
+    fn parses_panel_names() {
+        assert_eq!("Disks".parse::<Panel>(), Ok(Panel::Disks));
+        assert!("net".parse::<Panel>().is_err());
+    }
+}
@@ -1,77 +1,245 @@
+mod app;
+mod cli;
+mod collector;
+mod config;
+mod details;
+mod disks;
+mod diskstats;
+mod events;
+mod filter;
+mod history;
+mod keys;
+mod layout;
+mod network;
 mod processes;
+mod signals;
 mod stats;
-use crossterm::{
-    event::{self, Event, KeyCode},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
+mod terminal;
+mod theme;
+mod tree;
+mod users;
+use app::{App, Popup, Status};
+use clap::Parser;
+use cli::Cli;
+use collector::{Schedule, Snapshot};
+use crossterm::event::{Event, KeyEventKind};
+use events::AppEvent;
+use keys::Action;
+use layout::Panel;
 use std::{
     io::{self, Result},
-    time,
+    process,
+    sync::mpsc::{self, RecvTimeoutError},
 };
-use std::{thread, time::Duration};
-use sysinfo::{Disks, System};
+use sysinfo::System;
 use tui::{
-    backend::{Backend, CrosstermBackend},
-    layout::{Constraint, Direction, Layout},
-    Terminal,
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::Paragraph,
+    Frame, Terminal,
 };
 
-fn refresh_system(sys: &mut System) {
-    sys.refresh_all();
+fn render_status<B: Backend>(f: &mut Frame<B>, app: &App, chunk: Rect) {
+    if let Some(prompt) = &app.prompt {
+        let mut line = Spans::from(vec![
+            Span::styled(
+                app.prompt_label(&prompt.kind),
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::raw(prompt.input.clone()),
+            Span::styled(" ", Style::default().bg(Color::White)),
+        ]);
+        // errors from the input so far, e.g. an unfinished regex
+        if let Some(Status::Error(msg)) = &app.status {
+            line.0.push(Span::styled(
+                format!("  {msg}"),
+                Style::default().fg(Color::LightRed),
+            ));
+        }
+        f.render_widget(Paragraph::new(line), chunk);
+        return;
+    }
+
+    if app.zoom_pending {
+        f.render_widget(Paragraph::new(zoom_prompt(app)), chunk);
+        return;
+    }
+
+    let span = match &app.status {
+        Some(Status::Info(msg)) => {
+            Span::styled(msg.clone(), Style::default().fg(Color::LightGreen))
+        }
+        Some(Status::Error(msg)) => Span::styled(msg.clone(), Style::default().fg(Color::LightRed)),
+        None => Span::styled(app.keys.hint(), Style::default().fg(Color::DarkGray)),
+    };
+    f.render_widget(Paragraph::new(span), chunk);
 }
 
-fn ui<B: Backend>(terminal: &mut Terminal<B>, sys: &mut System, disks: &Disks) -> Result<()> {
+// "Zoom: 1 cpu  2 memory  ...", the keys that pick the panel to zoom
+fn zoom_prompt(app: &App) -> Spans<'static> {
+    let mut spans = vec![Span::styled("Zoom: ", Style::default().fg(Color::Yellow))];
+    for panel in Panel::ALL {
+        if let Some(key) = app.keys.key(Action::TogglePanel(panel)) {
+            spans.push(Span::raw(format!("{key} {}  ", panel.name())));
+        }
+    }
+    Spans::from(spans)
+}
+
+// "Stats (every 1s)", or "Stats (every 60s, 20s ago)" once the numbers are a
+// second old, the age shows how stale they are when sampling is slow or paused
+fn stats_title(app: &App, snapshot: &Snapshot) -> String {
+    let mut title = if app.paused {
+        "Stats (paused".to_string()
+    } else {
+        format!("Stats (every {}", details::format_interval(app.interval))
+    };
+    let (age, _) = details::rounded_age(snapshot.taken.elapsed());
+    if !age.is_zero() {
+        title.push_str(&format!(", {} ago", details::format_interval(age)));
+    }
+    title.push(')');
+    title
+}
+
+fn ui<B: Backend>(
+    terminal: &mut Terminal<B>,
+    snapshot: &Snapshot,
+    lookup: &System,
+    app: &mut App,
+) -> Result<()> {
     terminal.draw(|f| {
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .margin(1)
-            .constraints([Constraint::Length(40), Constraint::Length(50)].as_ref())
+        // leave the last line for the status bar
+        let screen = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
             .split(f.size());
 
-        stats::create_stats_chunk(f, sys, disks, chunks[0]);
-        processes::create_processes_chunk(f, sys, chunks[1]);
+        let main = screen[0].inner(&Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        let show_stats = Panel::ALL
+            .into_iter()
+            .any(|panel| panel != Panel::Processes && app.shows(panel));
+        let panels = layout::split_screen(main, show_stats, app.shows(Panel::Processes));
+        // popups for the table cover it, or the whole screen without it
+        let popup_area = panels.processes.unwrap_or(main);
+
+        if let Some(chunk) = panels.stats {
+            stats::create_stats_chunk(f, snapshot, app, stats_title(app, snapshot), chunk);
+        }
+        if let Some(chunk) = panels.processes {
+            processes::create_processes_chunk(f, &snapshot.processes, app, chunk);
+        }
+        render_status(f, app, screen[1]);
+
+        match &app.popup {
+            Some(Popup::SignalPicker { target, selected }) => {
+                signals::render_signal_picker(f, target, *selected, popup_area);
+            }
+            Some(Popup::ConfirmSignal { target, selected }) => {
+                signals::render_signal_confirm(f, target, *selected, popup_area);
+            }
+            Some(Popup::Details { pid, scroll }) => {
+                app.details_width = details::text_width(screen[0]);
+                let sample = snapshot.processes.iter().find(|p| p.pid == *pid);
+                details::render_process_details(f, lookup, *pid, sample, *scroll, screen[0]);
+            }
+            None => {}
+        }
     })?;
     Ok(())
 }
 
 fn main() -> Result<()> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-    let mut sys = System::new_all();
-    let disks = Disks::new_with_refreshed_list();
+    let cli = Cli::parse();
+    let config = match config::load(cli.config.as_deref()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("error: {err}");
+            process::exit(2);
+        }
+    };
+    let mut app = App::new();
+    // command line options override the config file
+    config.apply(&mut app);
+    cli.apply(&mut app);
 
-    let mut tick = 0;
+    terminal::install_panic_hook();
+    let mut schedule = Schedule {
+        interval: app.interval,
+        paused: app.paused,
+    };
+    let (sender, events) = mpsc::channel();
+    let collector = collector::spawn(schedule, sender.clone());
+    // the first sample takes a moment, wait for it before taking over the
+    // terminal so there is something to draw
+    let Ok(AppEvent::Snapshot(mut snapshot)) = events.recv() else {
+        return Err(io::Error::other("the collector thread stopped"));
+    };
+    app.history.push(&snapshot);
+    app.disks.update(&snapshot.disks, snapshot.taken);
+    let exit_sender = sender.clone();
+    terminal::on_exit_signal(move || {
+        let _ = exit_sender.send(AppEvent::Exit);
+    })?;
+    // restores the terminal when dropped, however main returns
+    let mut guard = terminal::TerminalGuard::new()?;
+    let terminal = &mut guard.terminal;
+    events::spawn_input(sender);
+    // looks up single processes for the details view and signals
+    let mut lookup = System::new();
 
+    ui(terminal, &snapshot, &lookup, &mut app)?;
     loop {
-        // every 10 ticks (1 sec) redraw tui
-        if tick % 10 == 0 {
-            refresh_system(&mut sys);
-            ui(&mut terminal, &mut sys, &disks)?;
-        }
-
-        // exit if q is pressed
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if key.code == KeyCode::Char('q') || key.code == KeyCode::Esc {
+        // sleeps until there is a new sample or terminal event, or until the
+        // sample age in the title changes with long intervals or while paused
+        let (_, age_changes) = details::rounded_age(snapshot.taken.elapsed());
+        let event = match events.recv_timeout(age_changes) {
+            Ok(event) => Some(event),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(io::Error::other("the collector and input threads stopped"));
+            }
+        };
+        match event {
+            Some(AppEvent::Snapshot(newer)) => {
+                snapshot = newer;
+                app.history.push(&snapshot);
+                app.disks.update(&snapshot.disks, snapshot.taken);
+                if let Some(Popup::Details { pid, .. }) = &app.popup {
+                    collector::refresh_process(&mut lookup, *pid);
+                }
+            }
+            // handle key presses, redraw right away so navigation feels responsive
+            Some(AppEvent::Terminal(Event::Key(key))) if key.kind == KeyEventKind::Press => {
+                app.handle_key(key, &mut lookup);
+                if !app.running {
                     break;
                 }
+                let changed = Schedule {
+                    interval: app.interval,
+                    paused: app.paused,
+                };
+                if changed != schedule {
+                    schedule = changed;
+                    collector.set_schedule(schedule);
+                }
+            }
+            // draw is called right away for the new size
+            Some(AppEvent::Terminal(Event::Resize(..))) | None => {}
+            Some(AppEvent::Terminal(_)) => continue,
+            Some(AppEvent::InputError(err)) => return Err(err),
+            Some(AppEvent::CollectorStopped) => {
+                return Err(io::Error::other("the collector thread stopped"));
             }
+            Some(AppEvent::Exit) => break,
         }
-
-        // each tick = 10 ms + computation amt
-        let sleep_time = time::Duration::from_millis(10);
-        thread::sleep(sleep_time);
-        tick += 1;
+        ui(terminal, &snapshot, &lookup, &mut app)?;
     }
 
-    // tear down terminal
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen,)?;
-    terminal.show_cursor()?;
-
     Ok(())
 }
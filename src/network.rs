@@ -0,0 +1,201 @@
+use crate::collector::NetworkSample;
+use crate::details::format_bytes;
+use crate::history::NetworkHistory;
+use crate::stats::{render_label_value, render_sparkline};
+use crate::theme::Theme;
+use std::collections::HashMap;
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+// per interface: name and rates, throughput graphs, totals, packets, errors,
+// mac, addresses and a blank line
+const INTERFACE_ROWS: u16 = 8;
+
+// rows the panel needs to show `count` interfaces: the border, the top margin
+// and the interfaces, the last blank line is the bottom margin
+pub fn panel_rows(count: u16) -> u16 {
+    3 + count.saturating_mul(INTERFACE_ROWS)
+}
+
+// 950, 12.3k, 4.6M
+#[allow(clippy::cast_precision_loss)]
+fn format_count(count: u64) -> String {
+    if count >= 1_000_000_000 {
+        format!("{:.1}G", count as f64 / 1_000_000_000.0)
+    } else if count >= 1_000_000 {
+        format!("{:.1}M", count as f64 / 1_000_000.0)
+    } else if count >= 1_000 {
+        format!("{:.1}k", count as f64 / 1_000.0)
+    } else {
+        count.to_string()
+    }
+}
+
+// "↓ 1.20 MB  ↑ 3.40 KB" with the arrows in the colors of the graphs
+fn received_transmitted(received: String, transmitted: String, theme: &Theme) -> Spans<'static> {
+    Spans::from(vec![
+        Span::styled("↓ ", Style::default().fg(theme.graph)),
+        Span::raw(received),
+        Span::styled("  ↑ ", Style::default().fg(theme.ok)),
+        Span::raw(transmitted),
+    ])
+}
+
+fn render_interface<B: Backend>(
+    f: &mut Frame<B>,
+    network: &NetworkSample,
+    history: Option<&NetworkHistory>,
+    theme: &Theme,
+    chunk: Rect,
+) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(1); usize::from(INTERFACE_ROWS)])
+        .split(chunk);
+    let columns = |row: Rect| {
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(25), Constraint::Percentage(75)].as_ref())
+            .split(row)
+    };
+
+    // name and current rates, the values are right aligned like the labels
+    // of the other panels
+    let name = Span::styled(
+        network.name.clone(),
+        Style::default().add_modifier(Modifier::BOLD),
+    );
+    let rates = received_transmitted(
+        format!("{}/s", format_bytes(network.receive_rate)),
+        format!("{}/s", format_bytes(network.transmit_rate)),
+        theme,
+    );
+    let chunks = columns(rows[0]);
+    f.render_widget(Paragraph::new(name), chunks[0]);
+    f.render_widget(Paragraph::new(rates).alignment(Alignment::Right), chunks[1]);
+
+    // received on the left, transmitted on the right
+    if let Some(history) = history {
+        let graphs = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(rows[1]);
+        let received: Vec<u64> = history.received.iter().copied().collect();
+        let transmitted: Vec<u64> = history.transmitted.iter().copied().collect();
+        render_sparkline(f, &received, None, theme.graph, graphs[0]);
+        render_sparkline(f, &transmitted, None, theme.ok, graphs[1]);
+    }
+
+    let totals = received_transmitted(
+        format_bytes(network.total_received),
+        format_bytes(network.total_transmitted),
+        theme,
+    );
+    let packets = received_transmitted(
+        format_count(network.packets_received),
+        format_count(network.packets_transmitted),
+        theme,
+    );
+    let mut errors = received_transmitted(
+        format_count(network.errors_received),
+        format_count(network.errors_transmitted),
+        theme,
+    );
+    if network.errors_received > 0 || network.errors_transmitted > 0 {
+        errors = Spans::from(
+            errors
+                .0
+                .into_iter()
+                .map(|span| Span::styled(span.content, Style::default().fg(theme.critical)))
+                .collect::<Vec<_>>(),
+        );
+    }
+    let lines = [
+        ("Total:", totals),
+        ("Packets:", packets),
+        ("Errors:", errors),
+    ];
+    for ((label, value), row) in lines.into_iter().zip(&rows[2..]) {
+        let chunks = columns(*row);
+        f.render_widget(Paragraph::new(label), chunks[0]);
+        f.render_widget(Paragraph::new(value).alignment(Alignment::Right), chunks[1]);
+    }
+
+    let chunks = columns(rows[5]);
+    render_label_value(f, "MAC:", network.mac_address.clone(), chunks[0], chunks[1]);
+    let addresses = if network.addresses.is_empty() {
+        "-".to_string()
+    } else {
+        network.addresses.join(", ")
+    };
+    let chunks = columns(rows[6]);
+    render_label_value(f, "IP:", addresses, chunks[0], chunks[1]);
+}
+
+// one section per interface in `networks`, as many as fit in `chunk`. the
+// title tells when some are left out
+pub fn render_network_stats<B: Backend>(
+    f: &mut Frame<B>,
+    networks: &[&NetworkSample],
+    history: &HashMap<String, NetworkHistory>,
+    theme: &Theme,
+    chunk: Rect,
+) {
+    let padding_chunk = Layout::default()
+        .direction(Direction::Horizontal)
+        .horizontal_margin(1)
+        .constraints([Constraint::Min(1)].as_ref())
+        .split(chunk);
+
+    // a blank line and a column of padding inside the border
+    let inner = Layout::default()
+        .direction(Direction::Vertical)
+        .vertical_margin(1)
+        .horizontal_margin(2)
+        .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+        .split(padding_chunk[0]);
+    // the blank line after the last interface may be cut off
+    let fit = usize::from((inner[1].height + 1) / INTERFACE_ROWS).min(networks.len());
+    let title = if fit < networks.len() {
+        format!("Network ({fit} of {})", networks.len())
+    } else {
+        "Network".to_string()
+    };
+    let outer_block = Block::default().title(title).borders(Borders::ALL);
+    f.render_widget(outer_block, padding_chunk[0]);
+
+    if networks.is_empty() {
+        f.render_widget(Paragraph::new("No network interfaces"), inner[1]);
+        return;
+    }
+
+    let networks = &networks[..fit];
+    let mut constraints = vec![Constraint::Length(INTERFACE_ROWS); fit];
+    constraints.push(Constraint::Min(0));
+    let interface_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(inner[1]);
+    for (network, chunk) in networks.iter().zip(interface_chunks.iter()) {
+        render_interface(f, network, history.get(&network.name), theme, *chunk);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_counts() {
+        assert_eq!(format_count(950), "950");
+        assert_eq!(format_count(12_345), "12.3k");
+        assert_eq!(format_count(4_560_000), "4.6M");
+        assert_eq!(format_count(7_000_000_000), "7.0G");
+    }
+}
@@ -1,46 +1,349 @@
-use sysinfo::{Process, System};
+use crate::app::App;
+use crate::collector;
+use crate::details;
+use crate::filter::ProcessFilter;
+use crate::tree::{self, TreeOptions};
+use crate::users::UserCache;
+use std::cmp::Ordering;
+use std::str::FromStr;
+use std::time::Duration;
+use sysinfo::{Pid, Process, ThreadKind};
 use tui::{
     backend::Backend,
     layout::{Constraint, Layout, Rect},
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
     widgets::{Block, Borders, Cell, Row, Table},
     Frame,
 };
 
+// shown for any value sysinfo could not read, e.g. the owner of a kernel thread
+pub const PLACEHOLDER: &str = "-";
+
+// everything the process table needs from a sysinfo Process, values sysinfo
+// cannot always read are optional
+#[derive(Clone)]
+pub struct ProcessInfo {
+    pub pid: Pid,
+    pub parent: Option<Pid>,
+    pub name: String,
+    pub cmd: Vec<String>,
+    pub memory: u64,
+    pub cpu_usage: f32,
+    pub run_time: u64,
+    pub user_id: Option<u32>,
+    pub group_id: Option<u32>,
+    // bytes per second since the previous sample
+    pub disk_read_rate: u64,
+    pub disk_write_rate: u64,
+    // userland threads share their process' memory
+    pub is_thread: bool,
+}
+
+impl ProcessInfo {
+    // `elapsed` is the time since the previous refresh, which the disk usage
+    // is counted over
+    pub fn new(process: &Process, elapsed: Duration) -> Self {
+        let disk_usage = process.disk_usage();
+        ProcessInfo {
+            pid: process.pid(),
+            parent: process.parent(),
+            name: process.name().to_string_lossy().to_string(),
+            cmd: process
+                .cmd()
+                .iter()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect(),
+            memory: process.memory(),
+            cpu_usage: process.cpu_usage(),
+            run_time: process.run_time(),
+            user_id: process.effective_user_id().map(|uid| **uid),
+            group_id: process.effective_group_id().map(|gid| *gid),
+            disk_read_rate: collector::rate(disk_usage.read_bytes, elapsed),
+            disk_write_rate: collector::rate(disk_usage.written_bytes, elapsed),
+            is_thread: process.thread_kind() == Some(ThreadKind::Userland),
+        }
+    }
+}
+
+// columns of the process table, in display order
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortColumn {
+    Pid,
+    Name,
+    Memory,
+    Cpu,
+    DiskRead,
+    DiskWrite,
+    Uptime,
+    User,
+}
+
+impl SortColumn {
+    pub const ALL: [SortColumn; 8] = [
+        SortColumn::Pid,
+        SortColumn::Name,
+        SortColumn::Memory,
+        SortColumn::Cpu,
+        SortColumn::DiskRead,
+        SortColumn::DiskWrite,
+        SortColumn::Uptime,
+        SortColumn::User,
+    ];
+
+    pub fn header(self) -> &'static str {
+        match self {
+            SortColumn::Pid => "PID",
+            SortColumn::Name => "Name",
+            SortColumn::Memory => "Mem (MB)",
+            SortColumn::Cpu => "CPU",
+            SortColumn::DiskRead => "Read/s",
+            SortColumn::DiskWrite => "Write/s",
+            SortColumn::Uptime => "Uptime (s)",
+            SortColumn::User => "User/Group",
+        }
+    }
+
+    // share of the table width, in percent of the full set of columns
+    fn width(self) -> u16 {
+        match self {
+            SortColumn::Pid => 7,
+            SortColumn::Name => 26,
+            SortColumn::Memory => 11,
+            SortColumn::Cpu => 9,
+            SortColumn::DiskRead => 10,
+            SortColumn::DiskWrite => 10,
+            SortColumn::Uptime => 12,
+            SortColumn::User => 15,
+        }
+    }
+
+    fn index(self) -> usize {
+        SortColumn::ALL.iter().position(|c| *c == self).unwrap()
+    }
+
+    pub fn compare(self, a: &ProcessInfo, b: &ProcessInfo, users: &UserCache) -> Ordering {
+        match self {
+            SortColumn::Pid => a.pid.cmp(&b.pid),
+            SortColumn::Name => a.name.cmp(&b.name),
+            SortColumn::Memory => a.memory.cmp(&b.memory),
+            SortColumn::Cpu => a.cpu_usage.total_cmp(&b.cpu_usage),
+            SortColumn::DiskRead => a.disk_read_rate.cmp(&b.disk_read_rate),
+            SortColumn::DiskWrite => a.disk_write_rate.cmp(&b.disk_write_rate),
+            SortColumn::Uptime => a.run_time.cmp(&b.run_time),
+            SortColumn::User => {
+                let names = |process: &ProcessInfo| {
+                    (
+                        process.user_id.map(|uid| users.user_name(uid)),
+                        process.group_id.map(|gid| users.group_name(gid)),
+                    )
+                };
+                names(a).cmp(&names(b))
+            }
+        }
+    }
+}
+
+pub fn sort_processes(
+    processes: &mut [&ProcessInfo],
+    column: SortColumn,
+    descending: bool,
+    users: &UserCache,
+) {
+    processes.sort_by(|a, b| {
+        let order = column.compare(a, b, users);
+        let order = if descending { order.reverse() } else { order };
+        // fall back to the pid so equal rows don't swap places between refreshes
+        order.then_with(|| a.pid.cmp(&b.pid))
+    });
+}
+
+impl FromStr for SortColumn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pid" => Ok(SortColumn::Pid),
+            "name" | "command" => Ok(SortColumn::Name),
+            "mem" | "memory" => Ok(SortColumn::Memory),
+            "cpu" => Ok(SortColumn::Cpu),
+            "read" => Ok(SortColumn::DiskRead),
+            "write" => Ok(SortColumn::DiskWrite),
+            "uptime" => Ok(SortColumn::Uptime),
+            "user" | "group" => Ok(SortColumn::User),
+            _ => Err(format!(
+                "unknown column {s:?}, expected pid, name, mem, cpu, read, write, uptime or user"
+            )),
+        }
+    }
+}
+
+// a process as shown in the table, in tree view memory and cpu usage are the
+// totals of the whole subtree
+pub struct ProcessRow<'a> {
+    pub process: &'a ProcessInfo,
+    // tree glyphs drawn before the name
+    pub prefix: String,
+    pub memory: u64,
+    pub cpu_usage: f32,
+}
+
+impl<'a> ProcessRow<'a> {
+    fn flat(process: &'a ProcessInfo) -> Self {
+        ProcessRow {
+            process,
+            prefix: String::new(),
+            memory: process.memory,
+            cpu_usage: process.cpu_usage,
+        }
+    }
+}
+
+// drops the directories of the executable and of any path argument, so the
+// script or jar being run stays visible in the narrow name column
+//   /usr/bin/python3 /srv/app/train.py --epochs 10 -> python3 train.py --epochs 10
+fn shorten_command(cmd: &[String]) -> String {
+    cmd.iter()
+        .map(|arg| {
+            if arg.starts_with('-') || !arg.contains('/') {
+                return arg.clone();
+            }
+            let trimmed = arg.trim_end_matches('/');
+            trimmed.rsplit('/').next().unwrap_or(trimmed).to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// splits the name so the part matching the filter is highlighted
+fn highlight_name(prefix: &str, name: String, filter: Option<&ProcessFilter>) -> Spans<'static> {
+    let prefix = Span::styled(prefix.to_string(), Style::default().fg(Color::DarkGray));
+    let Some(range) = filter.and_then(|filter| filter.name_match(&name)) else {
+        return Spans::from(vec![prefix, Span::raw(name)]);
+    };
+    // the name may have been truncated past the match
+    let end = range.end.min(name.len());
+    if range.start >= end || !name.is_char_boundary(range.start) || !name.is_char_boundary(end) {
+        return Spans::from(vec![prefix, Span::raw(name)]);
+    }
+
+    Spans::from(vec![
+        prefix,
+        Span::raw(name[..range.start].to_string()),
+        Span::styled(
+            name[range.start..end].to_string(),
+            Style::default().fg(Color::Black).bg(Color::Yellow),
+        ),
+        Span::raw(name[end..].to_string()),
+    ])
+}
+
+// "0", "512 B", "1.20 MB", short enough for the narrow rate columns
+fn format_rate(bytes: u64) -> String {
+    if bytes == 0 {
+        "0".to_string()
+    } else {
+        details::format_bytes(bytes)
+    }
+}
+
+// text of each column of a row, missing values are shown as PLACEHOLDER
 #[allow(clippy::cast_precision_loss)]
-fn add_process(_index: usize, process: &Process, rows: &mut Vec<Row>) {
-    let pid = process.pid().to_string();
-    // name should be truncated after 21 chars
-    let name = process
-        .name()
-        .to_string_lossy()
-        .to_string()
-        .chars()
-        .take(21)
-        .collect();
-    let mem = (process.memory() as f64) / (1_000_000.0);
+fn row_text(
+    row: &ProcessRow,
+    show_command: bool,
+    name_scroll: usize,
+    users: &UserCache,
+) -> [String; 8] {
+    let process = row.process;
+    let pid = process.pid.to_string();
+    let name: String = if show_command && !process.cmd.is_empty() {
+        // the table clips the command, scrolling shows the rest of it
+        shorten_command(&process.cmd)
+            .chars()
+            .skip(name_scroll)
+            .collect()
+    } else if process.name.is_empty() {
+        PLACEHOLDER.to_string()
+    } else {
+        // name should be truncated after 21 chars
+        process.name.chars().take(21).collect()
+    };
+    let mem = (row.memory as f64) / (1_000_000.0);
     let mem_fmt = format!("{mem:.2}");
-    let cpu_usage = format!("{:.2}%", process.cpu_usage());
-    let uptime = format!("{}", process.run_time());
-    let euid_egid_fmt = format!(
+    let cpu_usage = format!("{:.2}%", row.cpu_usage);
+    let disk_read = format_rate(process.disk_read_rate);
+    let disk_write = format_rate(process.disk_write_rate);
+    let uptime = format!("{}", process.run_time);
+    let user_group = format!(
         "{}/{}",
-        **process.effective_user_id().unwrap(),
-        *process.effective_group_id().unwrap()
+        process
+            .user_id
+            .map_or_else(|| PLACEHOLDER.to_string(), |uid| users.user_name(uid)),
+        process
+            .group_id
+            .map_or_else(|| PLACEHOLDER.to_string(), |gid| users.group_name(gid))
     );
+    [
+        pid, name, mem_fmt, cpu_usage, disk_read, disk_write, uptime, user_group,
+    ]
+}
 
-    let cells = vec![pid, name, mem_fmt, cpu_usage, uptime, euid_egid_fmt]
-        .into_iter()
-        .map(Cell::from);
+// how far the commands can be scrolled before the longest one is gone from a
+// name column `width` characters wide
+fn max_name_scroll(rows: &[ProcessRow], width: usize) -> usize {
+    rows.iter()
+        .filter(|row| !row.process.cmd.is_empty())
+        .map(|row| row.prefix.chars().count() + shorten_command(&row.process.cmd).chars().count())
+        .max()
+        .unwrap_or(0)
+        .saturating_sub(width)
+}
 
-    let row = Row::new(cells);
-    rows.push(row);
+fn add_process(_index: usize, row: &ProcessRow, app: &App, rows: &mut Vec<Row>) {
+    let mut text = row_text(row, app.show_command, app.name_scroll, &app.users);
+    let cells = app.columns.iter().map(|column| {
+        let value = std::mem::take(&mut text[column.index()]);
+        if *column == SortColumn::Name {
+            Cell::from(highlight_name(&row.prefix, value, app.filter.as_ref()))
+        } else {
+            Cell::from(value)
+        }
+    });
+    rows.push(Row::new(cells));
 }
 
-pub fn create_processes_chunk<B: Backend>(f: &mut Frame<B>, sys: &mut System, chunk: Rect) {
+pub fn create_processes_chunk<B: Backend>(
+    f: &mut Frame<B>,
+    infos: &[ProcessInfo],
+    app: &mut App,
+    chunk: Rect,
+) {
+    let mut title = if app.top_mode {
+        format!(
+            "Processes{} (top {} by {}",
+            if app.tree_view { " tree" } else { "" },
+            app.top_n,
+            app.sort_column.header()
+        )
+    } else {
+        format!(
+            "Processes{} (>{} MB",
+            if app.tree_view { " tree" } else { "" },
+            app.min_memory / 1_000_000
+        )
+    };
+    if app.filter.is_some() {
+        title.push_str(&format!(", filter: {}", app.filter_text));
+    }
+    if let Some(ProcessFilter::User(user)) = &app.user_filter {
+        title.push_str(&format!(", user: {user}"));
+    }
+    title.push(')');
     let outer_chunk = Block::default()
         .borders(Borders::ALL)
-        .title("Processes")
-        .border_style(Style::default().fg(tui::style::Color::White));
+        .title(title)
+        .border_style(Style::default().fg(app.theme.border));
     f.render_widget(outer_chunk, chunk);
 
     // table goes here
@@ -51,44 +354,238 @@ pub fn create_processes_chunk<B: Backend>(f: &mut Frame<B>, sys: &mut System, ch
         .constraints([Constraint::Min(1)].as_ref())
         .split(chunk);
 
-    let min_memory_usage = 50_000_000; // ignore any processes <50 MB
-    sys.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
-    let mut processes: Vec<_> = sys
-        .processes()
-        .values()
-        .filter(|process| process.memory() > min_memory_usage)
-        .collect();
-    // sort by memory size in descending order
-    processes.sort_by_key(|b| std::cmp::Reverse(b.memory()));
+    app.users.refresh_for(
+        infos
+            .iter()
+            .map(|process| (process.user_id, process.group_id)),
+    );
+    // top N mode ignores the memory threshold entirely
+    let min_memory = if app.top_mode { 0 } else { app.min_memory };
+    let processes: Vec<ProcessRow> = if app.tree_view {
+        tree::build_tree(
+            infos,
+            &TreeOptions {
+                min_memory,
+                matches: &|process| app.matches(process),
+                top_n: app.top_mode.then_some(app.top_n),
+                sort_column: app.sort_column,
+                sort_descending: app.sort_descending,
+                collapsed: &app.collapsed,
+                users: &app.users,
+            },
+        )
+    } else {
+        // threads repeat their process' memory, like in the tree they are
+        // left out
+        let mut processes: Vec<_> = infos
+            .iter()
+            .filter(|process| !process.is_thread)
+            .filter(|process| process.memory >= min_memory)
+            .filter(|process| app.matches(process))
+            .collect();
+        sort_processes(
+            &mut processes,
+            app.sort_column,
+            app.sort_descending,
+            &app.users,
+        );
+        if app.top_mode {
+            processes.truncate(app.top_n);
+        }
+        processes.into_iter().map(ProcessRow::flat).collect()
+    };
 
-    let header_cells = ["PID", "Name", "Mem (MB)", "CPU", "Uptime (s)", "EUID/EGID"]
+    // hidden columns give their share of the width to the visible ones
+    let total_width: u16 = app.columns.iter().map(|column| column.width()).sum();
+    let percent = |column: &SortColumn| column.width() * 100 / total_width.max(1);
+    let widths: Vec<Constraint> = app
+        .columns
         .iter()
-        .map(|h| {
-            Cell::from(*h).style(
-                Style::default()
-                    .fg(tui::style::Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            )
-        });
-    let header = Row::new(header_cells);
+        .map(|column| Constraint::Percentage(percent(column)))
+        .collect();
+
+    // scrolling stops once the end of the longest command is in view, the
+    // table leaves a column of space between its columns
+    if app.show_command {
+        let spacing = u16::try_from(app.columns.len().saturating_sub(1)).unwrap_or(u16::MAX);
+        let name_width =
+            inner_chunk[0].width.saturating_sub(spacing) * percent(&SortColumn::Name) / 100;
+        let max_scroll = max_name_scroll(&processes, usize::from(name_width));
+        app.name_scroll = app.name_scroll.min(max_scroll);
+    }
+
+    // mark the sorted column with the sort direction
+    let header_cells = app.columns.iter().map(|column| {
+        let mut title = column.header().to_string();
+        if app.show_command && *column == SortColumn::Name {
+            title = if app.name_scroll > 0 {
+                format!("Command (+{})", app.name_scroll)
+            } else {
+                "Command".to_string()
+            };
+        }
+        // memory and cpu are summed over the subtree in tree view
+        if app.tree_view && matches!(column, SortColumn::Memory | SortColumn::Cpu) {
+            title.push('Σ');
+        }
+        if *column == app.sort_column {
+            let marker = if app.sort_descending { '▼' } else { '▲' };
+            title = format!("{title} {marker}");
+        }
+        Cell::from(title).style(
+            Style::default()
+                .fg(app.theme.header)
+                .add_modifier(Modifier::BOLD),
+        )
+    });
+    let header = Row::new(header_cells).bottom_margin(1);
     let mut process_rows: Vec<Row> = vec![];
-    process_rows.push(Row::new(vec![Cell::from("")]));
 
-    for (index, process) in processes.iter().enumerate() {
-        add_process(index, process, &mut process_rows);
+    // keep the selection on the same pid even if the order changed
+    app.sync_processes(processes.iter().map(|row| row.process.pid).collect());
+    // header and its margin take up two rows
+    app.page_size = usize::from(inner_chunk[0].height.saturating_sub(2)).max(1);
+
+    for (index, row) in processes.iter().enumerate() {
+        add_process(index, row, app, &mut process_rows);
     }
 
     let table = Table::new(process_rows)
         .header(header)
         .block(Block::default().borders(Borders::NONE))
-        .widths(&[
-            Constraint::Percentage(8),  // pid
-            Constraint::Percentage(32), // name
-            Constraint::Percentage(13), // memory
-            Constraint::Percentage(10), // cpu
-            Constraint::Percentage(16), // uptime
-            Constraint::Percentage(19), // euid/egid
-        ]);
-
-    f.render_widget(table, inner_chunk[0]);
+        .highlight_style(
+            Style::default()
+                .bg(app.theme.highlight)
+                .add_modifier(Modifier::BOLD),
+        )
+        .widths(&widths);
+
+    f.render_stateful_widget(table, inner_chunk[0], &mut app.process_table);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    // a process like a kernel thread, where sysinfo could read nothing but the pid
+    fn bare_process(pid: u32) -> ProcessInfo {
+        ProcessInfo {
+            pid: Pid::from_u32(pid),
+            parent: None,
+            name: String::new(),
+            cmd: Vec::new(),
+            memory: 0,
+            cpu_usage: 0.0,
+            run_time: 0,
+            user_id: None,
+            group_id: None,
+            disk_read_rate: 0,
+            disk_write_rate: 0,
+            is_thread: false,
+        }
+    }
+
+    fn users() -> UserCache {
+        UserCache::from_names(
+            HashMap::from([(0, "root".to_string()), (1000, "alice".to_string())]),
+            HashMap::from([(0, "root".to_string())]),
+        )
+    }
+
+    fn text(process: &ProcessInfo, show_command: bool) -> [String; 8] {
+        row_text(&ProcessRow::flat(process), show_command, 0, &users())
+    }
+
+    #[test]
+    fn missing_fields_render_as_placeholders() {
+        let [pid, name, mem, cpu, read, write, uptime, user_group] = text(&bare_process(42), false);
+        assert_eq!(pid, "42");
+        assert_eq!(name, PLACEHOLDER);
+        assert_eq!(mem, "0.00");
+        assert_eq!(cpu, "0.00%");
+        assert_eq!(read, "0");
+        assert_eq!(write, "0");
+        assert_eq!(uptime, "0");
+        assert_eq!(user_group, "-/-");
+    }
+
+    #[test]
+    fn missing_group_only_replaces_group() {
+        let process = ProcessInfo {
+            user_id: Some(1000),
+            ..bare_process(1)
+        };
+        assert_eq!(text(&process, false)[7], "alice/-");
+    }
+
+    #[test]
+    fn unknown_ids_fall_back_to_numbers() {
+        let process = ProcessInfo {
+            user_id: Some(1017),
+            group_id: Some(0),
+            ..bare_process(1)
+        };
+        assert_eq!(text(&process, false)[7], "1017/root");
+    }
+
+    #[test]
+    fn command_without_args_falls_back_to_name() {
+        let process = ProcessInfo {
+            name: "kworker/0:1".to_string(),
+            ..bare_process(1)
+        };
+        assert_eq!(text(&process, true)[1], "kworker/0:1");
+        assert_eq!(text(&bare_process(1), true)[1], PLACEHOLDER);
+    }
+
+    #[test]
+    fn long_names_are_truncated() {
+        let process = ProcessInfo {
+            name: "a-very-long-process-name-indeed".to_string(),
+            ..bare_process(1)
+        };
+        assert_eq!(text(&process, false)[1], "a-very-long-process-n");
+    }
+
+    #[test]
+    fn command_keeps_script_name() {
+        let process = ProcessInfo {
+            cmd: vec![
+                "/usr/bin/python3".to_string(),
+                "/srv/app/train.py".to_string(),
+                "--out=/tmp/x".to_string(),
+            ],
+            ..bare_process(1)
+        };
+        assert_eq!(text(&process, true)[1], "python3 train.py --out=/tmp/x");
+    }
+
+    #[test]
+    fn command_scroll_stops_at_the_longest_command() {
+        let long = ProcessInfo {
+            cmd: vec!["python3".to_string(), "train.py".to_string()],
+            ..bare_process(1)
+        };
+        let short = ProcessInfo {
+            cmd: vec!["sh".to_string()],
+            ..bare_process(2)
+        };
+        let rows = [ProcessRow::flat(&long), ProcessRow::flat(&short)];
+        // "python3 train.py" is 16 characters
+        assert_eq!(max_name_scroll(&rows, 10), 6);
+        assert_eq!(max_name_scroll(&rows, 20), 0);
+    }
+
+    #[test]
+    fn sorting_by_user_handles_missing_ids() {
+        let with_user = ProcessInfo {
+            user_id: Some(0),
+            ..bare_process(2)
+        };
+        let without_user = bare_process(1);
+        let mut processes = vec![&with_user, &without_user];
+        sort_processes(&mut processes, SortColumn::User, false, &users());
+        assert_eq!(processes[0].pid, without_user.pid);
+    }
 }
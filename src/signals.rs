@@ -0,0 +1,149 @@
+use std::io;
+use sysinfo::{Pid, ProcessesToUpdate, Signal, System};
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+// signals offered by the picker, in the order they are listed
+pub const SIGNALS: [(&str, Signal); 8] = [
+    ("SIGTERM", Signal::Term),
+    ("SIGKILL", Signal::Kill),
+    ("SIGSTOP", Signal::Stop),
+    ("SIGCONT", Signal::Continue),
+    ("SIGHUP", Signal::Hangup),
+    ("SIGINT", Signal::Interrupt),
+    ("SIGUSR1", Signal::User1),
+    ("SIGUSR2", Signal::User2),
+];
+
+// the process a signal will be sent to, captured when the picker is opened so
+// a refresh reordering the table cannot change the target
+#[derive(Clone)]
+pub struct SignalTarget {
+    pub pid: Pid,
+    pub name: String,
+}
+
+// send the signal at `index` of SIGNALS to the target, returning the message
+// shown in the status line. `sys` only holds the processes the ui looked up,
+// so the target is refreshed first to check it still exists
+pub fn send_signal(
+    sys: &mut System,
+    target: &SignalTarget,
+    index: usize,
+) -> Result<String, String> {
+    let (signal_name, signal) = SIGNALS[index];
+    sys.refresh_processes(ProcessesToUpdate::Some(&[target.pid]), true);
+    let Some(process) = sys.process(target.pid) else {
+        return Err(format!("Process {} no longer exists", target.pid));
+    };
+
+    match process.kill_with(signal) {
+        Some(true) => Ok(format!(
+            "Sent {signal_name} to {} ({})",
+            target.pid, target.name
+        )),
+        Some(false) => {
+            // sysinfo only reports success, errno still holds the reason
+            let err = io::Error::last_os_error();
+            Err(format!(
+                "Failed to send {signal_name} to {} ({}): {err}",
+                target.pid, target.name
+            ))
+        }
+        None => Err(format!("{signal_name} is not supported on this platform")),
+    }
+}
+
+// returns a rect of the given size centered in `area`, shrunk to fit if needed
+pub fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+pub fn render_signal_picker<B: Backend>(
+    f: &mut Frame<B>,
+    target: &SignalTarget,
+    selected: usize,
+    area: Rect,
+) {
+    // 8 signals + 2 border rows
+    let popup = centered_rect(36, 10, area);
+    f.render_widget(Clear, popup);
+
+    let items: Vec<ListItem> = SIGNALS
+        .iter()
+        .map(|(name, signal)| ListItem::new(format!("{name:<8} {signal}")))
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(format!("Signal {} ({})", target.pid, target.name))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("> ");
+
+    let mut state = ListState::default();
+    state.select(Some(selected));
+    f.render_stateful_widget(list, popup, &mut state);
+}
+
+pub fn render_signal_confirm<B: Backend>(
+    f: &mut Frame<B>,
+    target: &SignalTarget,
+    selected: usize,
+    area: Rect,
+) {
+    let popup = centered_rect(44, 6, area);
+    f.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .title("Confirm")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::LightRed));
+    f.render_widget(block, popup);
+
+    let text_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(1), // question
+            Constraint::Length(1), // spacing
+            Constraint::Length(1), // answers
+        ])
+        .split(popup);
+
+    let question = Spans::from(vec![
+        Span::raw("Send "),
+        Span::styled(
+            SIGNALS[selected].0,
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(format!(" to {} ({})?", target.pid, target.name)),
+    ]);
+    f.render_widget(
+        Paragraph::new(question).alignment(Alignment::Center),
+        text_chunks[0],
+    );
+    f.render_widget(
+        Paragraph::new("[y] Yes    [n] No").alignment(Alignment::Center),
+        text_chunks[2],
+    );
+}
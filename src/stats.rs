@@ -1,46 +1,52 @@
-use sysinfo::{Cpu, Disks, System};
+use crate::app::App;
+use crate::collector::{CpuSample, NetworkSample, Snapshot, SystemInfo};
+use crate::details::format_bytes;
+use crate::disks;
+use crate::diskstats::DiskIoSample;
+use crate::history::History;
+use crate::layout::Panel;
+use crate::network;
+use crate::theme::Theme;
+use std::collections::VecDeque;
+use std::time::Instant;
 use tui::{
     backend::Backend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
+    symbols,
     text::{Span, Spans},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{
+        Axis, Block, Borders, Cell, Chart, Dataset, GraphType, Paragraph, Row, Sparkline, Table,
+    },
     Frame,
 };
 
-fn get_global_cpu_usage(sys: &System) -> f32 {
-    sys.global_cpu_usage()
+// rows of the global cpu usage chart
+const CPU_CHART_HEIGHT: u16 = 4;
+// rows of the memory and swap numbers, including the margins, and of the
+// history charts below them
+const MEM_ROWS: u16 = 8;
+const MEM_CHART_HEIGHT: u16 = 3;
+const SWP_ROWS: u16 = 7;
+const SWP_CHART_HEIGHT: u16 = 3;
+const SYSTEM_ROWS: u16 = 9;
+// "cpu12 100%  "
+const GRID_CELL_WIDTH: u16 = 12;
+
+fn color_severity(s: String, num: f32, theme: &Theme) -> Span<'static> {
+    // change the color of s based on which category the percentge is in,
+    // the thresholds come from the theme (75.5% and 50% by default)
+    Span::styled(s, Style::default().fg(theme.severity(num)))
 }
 
-fn get_individual_cpus(sys: &System) -> Vec<&Cpu> {
-    let mut individual_cpus: Vec<&Cpu> = Vec::new();
-    for cpu in sys.cpus() {
-        individual_cpus.push(cpu);
-    }
-    individual_cpus
-}
-
-fn color_severity(s: String, num: f32) -> Span<'static> {
-    // change the color of s based on which category the percentge is in
-    // >75% -> Red
-    // >50% -> Yellow
-    // rest -> Green
-    if num > 75.5 {
-        return Span::styled(s, Style::default().fg(Color::LightRed));
-    } else if num > 50.0 {
-        return Span::styled(s, Style::default().fg(Color::LightYellow));
-    }
-    return Span::styled(s, Style::default().fg(Color::LightGreen));
-}
-
-fn render_label_value<B: Backend>(
+pub fn render_label_value<'a, B: Backend>(
     f: &mut Frame<B>,
-    label: &str,
+    label: impl Into<Span<'a>>,
     value: String,
     label_chunk: Rect,
     value_chunk: Rect,
 ) {
-    let label_paragraph = Paragraph::new(Span::styled(label, Style::default()))
+    let label_paragraph = Paragraph::new(Spans::from(label.into()))
         .block(Block::default().borders(Borders::NONE))
         .alignment(Alignment::Left);
     let value_paragraph = Paragraph::new(Span::styled(value, Style::default()))
@@ -50,68 +56,169 @@ fn render_label_value<B: Backend>(
     f.render_widget(value_paragraph, value_chunk);
 }
 
-#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
 fn render_individual_cpu<B: Backend>(
     f: &mut Frame<B>,
-    cpu: &Cpu,
+    cpu: &CpuSample,
+    history: &VecDeque<f32>,
+    theme: &Theme,
     percent_chunk: Rect,
-    bar_chunk: Rect,
+    graph_chunk: Rect,
 ) {
     // render percentage chunk (CPU #: XX.XX%)
-    let prefix = Span::styled(format!("CPU {}: ", cpu.name()), Style::default());
-    let percent = color_severity(format!("{:.2}%", cpu.cpu_usage()), cpu.cpu_usage());
+    let prefix = Span::styled(format!("CPU {}: ", cpu.name), Style::default());
+    let percent = color_severity(format!("{:.2}%", cpu.usage), cpu.usage, theme);
     let formatted_percent = Spans::from(vec![prefix, percent]);
     let percent_paragraph = Paragraph::new(formatted_percent)
         .block(Block::default().borders(Borders::NONE))
         .alignment(Alignment::Left);
     f.render_widget(percent_paragraph, percent_chunk);
 
-    // render a percentage bar to provide another representation of the cpu usage
-    let rounded = cpu.cpu_usage().round() as u32;
-    let num_bars: usize = rounded.div_ceil(10) as usize;
-    let mut severity_string = "|".repeat(num_bars);
-    while severity_string.len() < 10 {
-        severity_string.push(' ');
+    // render the recent usage of the core
+    let values: Vec<u64> = history.iter().map(|usage| usage.round() as u64).collect();
+    render_sparkline(
+        f,
+        &values,
+        Some(100),
+        theme.severity(cpu.usage),
+        graph_chunk,
+    );
+}
+
+// sparkline of the most recent `values`, newest on the right. without `max`
+// it is scaled to the largest value shown
+pub fn render_sparkline<B: Backend>(
+    f: &mut Frame<B>,
+    values: &[u64],
+    max: Option<u64>,
+    color: Color,
+    chunk: Rect,
+) {
+    // the sparkline draws from the start of the data, so only the values
+    // that fit are kept
+    let values = &values[values.len().saturating_sub(usize::from(chunk.width))..];
+    let mut sparkline = Sparkline::default()
+        .data(values)
+        .style(Style::default().fg(color));
+    if let Some(max) = max {
+        sparkline = sparkline.max(max);
     }
-    let severity_bar = color_severity(severity_string, cpu.cpu_usage());
-    let severity_span = Spans::from(vec![
-        Span::styled("[ ".to_string(), Style::default()),
-        severity_bar,
-        Span::styled(" ]".to_string(), Style::default()),
-    ]);
-    let bar_paragraph = Paragraph::new(severity_span)
-        .block(Block::default().borders(Borders::NONE))
-        .alignment(Alignment::Right);
-    f.render_widget(bar_paragraph, bar_chunk);
+    // right aligned until the history fills the width
+    let width = u16::try_from(values.len()).unwrap_or(u16::MAX);
+    let chunk = Rect {
+        x: chunk.right().saturating_sub(width),
+        width: width.min(chunk.width),
+        ..chunk
+    };
+    f.render_widget(sparkline, chunk);
+}
+
+// line chart of percentages over the whole history, newest on the right
+#[allow(clippy::cast_precision_loss)]
+fn render_history_chart<B: Backend>(
+    f: &mut Frame<B>,
+    len: usize,
+    lines: &[(&VecDeque<f32>, Color)],
+    chunk: Rect,
+) {
+    // samples are placed from the right so the newest is always at the edge
+    let data: Vec<Vec<(f64, f64)>> = lines
+        .iter()
+        .map(|(values, _)| {
+            let offset = len - values.len();
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| ((offset + i) as f64, f64::from(*value)))
+                .collect()
+        })
+        .collect();
+    let datasets = data
+        .iter()
+        .zip(lines)
+        .map(|(data, (_, color))| {
+            Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(*color))
+                .data(data)
+        })
+        .collect();
+    let label_style = Style::default().fg(Color::DarkGray);
+    let chart = Chart::new(datasets)
+        .x_axis(Axis::default().bounds([0.0, (len - 1) as f64]))
+        .y_axis(Axis::default().bounds([0.0, 100.0]).labels(vec![
+            Span::styled("0", label_style),
+            Span::styled("100", label_style),
+        ]));
+    f.render_widget(chart, chunk);
 }
 
-fn render_cpu_stats<B: Backend>(f: &mut Frame<B>, sys: &System, cpus: &[&Cpu], chunk: Rect) {
+fn render_cpu_stats<B: Backend>(
+    f: &mut Frame<B>,
+    snapshot: &Snapshot,
+    history: &History,
+    (layout, core_rows): (CoreLayout, u16),
+    theme: &Theme,
+    chunk: Rect,
+) {
+    // the chart takes any rows the cores do not need
     let cpu_chunk = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
-        .constraints([Constraint::Length(2), Constraint::Length(10)])
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Min(CPU_CHART_HEIGHT),
+            Constraint::Length(core_rows),
+        ])
         .split(chunk);
 
     // render global cpu stats
-    let global_usage: f32 = get_global_cpu_usage(sys);
+    let global_usage: f32 = snapshot.global_cpu_usage;
     let prefix = Span::styled("Global CPU Usage: ".to_string(), Style::default());
-    let percentage = color_severity(format!("{global_usage:.2}%"), global_usage);
+    let percentage = color_severity(format!("{global_usage:.2}%"), global_usage, theme);
     let global_percentage = Spans::from(vec![prefix, percentage]);
     let global_percentage_paragraph = Paragraph::new(global_percentage)
         .block(Block::default().borders(Borders::NONE))
         .alignment(Alignment::Left);
     f.render_widget(global_percentage_paragraph, cpu_chunk[0]);
+    render_history_chart(
+        f,
+        history.len(),
+        &[(&history.global_cpu, theme.severity(global_usage))],
+        cpu_chunk[1],
+    );
 
-    // render individual cpu stats
-    let num_cpus = cpus.len();
-    let constraints = vec![Constraint::Length(1); num_cpus];
+    match layout {
+        CoreLayout::Rows => render_core_rows(f, snapshot, history, theme, cpu_chunk[2]),
+        CoreLayout::Grid { columns } => {
+            render_core_grid(f, snapshot, columns, theme, cpu_chunk[2]);
+        }
+        CoreLayout::Heatmap { columns } => {
+            render_core_heatmap(f, snapshot, columns, theme, cpu_chunk[2]);
+        }
+    }
+}
+
+// one line per core with its usage and history
+fn render_core_rows<B: Backend>(
+    f: &mut Frame<B>,
+    snapshot: &Snapshot,
+    history: &History,
+    theme: &Theme,
+    chunk: Rect,
+) {
+    let num_cpus = snapshot.cpus.len();
+    // the filler keeps the last row from stretching over spare rows
+    let mut constraints = vec![Constraint::Length(1); num_cpus];
+    constraints.push(Constraint::Min(0));
 
     let individual_cpu_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .margin(0)
-        //                         CPU #: XX.XX%    [|||       ]
+        //                         CPU #: XX.XX%    ▂▃▅▂▁▁▇█
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-        .split(cpu_chunk[1]);
+        .split(chunk);
 
     let individual_cpu_percents_chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -119,29 +226,160 @@ fn render_cpu_stats<B: Backend>(f: &mut Frame<B>, sys: &System, cpus: &[&Cpu], c
         .constraints(constraints.clone())
         .split(individual_cpu_chunks[0]);
 
-    let individual_cpu_bars_chunks = Layout::default()
+    let individual_cpu_graph_chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(0)
         .constraints(constraints)
         .split(individual_cpu_chunks[1]);
 
     // add cpu percentages
-    for (i, cpu) in get_individual_cpus(sys).iter().enumerate() {
+    let no_history = VecDeque::new();
+    for (i, cpu) in snapshot.cpus.iter().enumerate() {
         render_individual_cpu(
             f,
             cpu,
+            history.cores.get(i).unwrap_or(&no_history),
+            theme,
             individual_cpu_percents_chunks[i],
-            individual_cpu_bars_chunks[i],
+            individual_cpu_graph_chunks[i],
         );
     }
 }
 
+// cores side by side, row by row: "cpu0   12%  cpu1    3%"
+fn render_core_grid<B: Backend>(
+    f: &mut Frame<B>,
+    snapshot: &Snapshot,
+    columns: usize,
+    theme: &Theme,
+    chunk: Rect,
+) {
+    let lines: Vec<Spans> = snapshot
+        .cpus
+        .chunks(columns)
+        .map(|row| {
+            let cells = row.iter().flat_map(|cpu| {
+                [
+                    Span::raw(format!("{:<6}", cpu.name)),
+                    color_severity(
+                        format!("{:>4}  ", format!("{:.0}%", cpu.usage)),
+                        cpu.usage,
+                        theme,
+                    ),
+                ]
+            });
+            Spans::from(cells.collect::<Vec<_>>())
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), chunk);
+}
+
+// one character per core, denser and colored by usage
+fn render_core_heatmap<B: Backend>(
+    f: &mut Frame<B>,
+    snapshot: &Snapshot,
+    columns: usize,
+    theme: &Theme,
+    chunk: Rect,
+) {
+    let lines: Vec<Spans> = snapshot
+        .cpus
+        .chunks(columns)
+        .map(|row| {
+            let cells = row
+                .iter()
+                .map(|cpu| color_severity(heat_symbol(cpu.usage).to_string(), cpu.usage, theme));
+            Spans::from(cells.collect::<Vec<_>>())
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), chunk);
+}
+
+// an idle core still gets a visible cell
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn heat_symbol(usage: f32) -> char {
+    const SYMBOLS: [char; 5] = ['·', '░', '▒', '▓', '█'];
+    let level = (usage / 100.0 * 4.0).round().clamp(0.0, 4.0) as usize;
+    SYMBOLS[level]
+}
+
+// heights of the panels in `height` rows from the (least, most) rows each
+// wants, in order of importance with (0, 0) for the hidden ones. panels
+// that do not fit are dropped starting with the least important one, except
+// the first which is cut off instead. spare rows go to the more important
+// panels first, and a panel left on its own takes all of them
+fn panel_heights<const N: usize>(height: u16, wanted: [(u16, u16); N]) -> [u16; N] {
+    let mut heights = wanted.map(|(least, _)| least);
+    for i in (1..N).rev() {
+        if heights.iter().sum::<u16>() <= height {
+            break;
+        }
+        heights[i] = 0;
+    }
+
+    let mut spare = height.saturating_sub(heights.iter().sum());
+    for (height, (_, most)) in heights.iter_mut().zip(wanted) {
+        if *height > 0 {
+            let grow = most.saturating_sub(*height).min(spare);
+            *height += grow;
+            spare -= grow;
+        }
+    }
+    let mut visible = heights.iter_mut().filter(|height| **height > 0);
+    if let (Some(alone), None) = (visible.next(), visible.next()) {
+        *alone = height;
+    }
+    heights
+}
+
+// how the per-core usage is drawn, depending on the number of cores and the
+// space they get
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CoreLayout {
+    Rows,
+    Grid { columns: usize },
+    Heatmap { columns: usize },
+}
+
+// picks the most detailed layout that fits `cores` into `width` columns and
+// at most `max_rows` lines, returning it with the number of lines it needs.
+// the heatmap is cut off if even that does not fit
+fn core_layout(cores: usize, width: u16, max_rows: u16) -> (CoreLayout, u16) {
+    let rows_for =
+        |columns: usize| u16::try_from(cores.div_ceil(columns.max(1))).unwrap_or(u16::MAX);
+    let rows = rows_for(1);
+    if rows <= max_rows {
+        return (CoreLayout::Rows, rows);
+    }
+    let columns = usize::from(width / GRID_CELL_WIDTH);
+    if columns > 1 && rows_for(columns) <= max_rows {
+        return (CoreLayout::Grid { columns }, rows_for(columns));
+    }
+    let columns = usize::from(width).max(1);
+    (
+        CoreLayout::Heatmap { columns },
+        rows_for(columns).min(max_rows),
+    )
+}
+
 #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
-fn render_mem_stats<B: Backend>(f: &mut Frame<B>, sys: &System, chunk: Rect) {
-    let total_mem = sys.total_memory();
-    let used_mem = sys.used_memory();
-    let avail_mem = sys.available_memory();
-    let free_mem = sys.free_memory();
+fn render_mem_stats<B: Backend>(
+    f: &mut Frame<B>,
+    snapshot: &Snapshot,
+    history: &History,
+    theme: &Theme,
+    chunk: Rect,
+) {
+    let total_mem = snapshot.total_memory;
+    let used_mem = snapshot.used_memory;
+    let avail_mem = snapshot.available_memory;
+    let free_mem = snapshot.free_memory;
+
+    // numbers on top, history of used and available memory below
+    let mem_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(MEM_ROWS), Constraint::Min(0)])
+        .split(chunk);
 
     let mem_sub_chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -150,7 +388,7 @@ fn render_mem_stats<B: Backend>(f: &mut Frame<B>, sys: &System, chunk: Rect) {
         //                         Total Memory:     XX.XX GB
         //                         ...
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-        .split(chunk);
+        .split(mem_chunks[0]);
 
     let mem_label_chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -181,7 +419,7 @@ fn render_mem_stats<B: Backend>(f: &mut Frame<B>, sys: &System, chunk: Rect) {
     // render global mem percentage
     let prefix = Span::styled("Memory: ".to_string(), Style::default());
     let percent = ((used_mem as f64 / total_mem as f64) * 100.0) as f32;
-    let percent_color = color_severity(format!("{percent:.2}%"), percent);
+    let percent_color = color_severity(format!("{percent:.2}%"), percent, theme);
     let formatted_percent = Spans::from(vec![prefix, percent_color]);
     let percent_paragraph = Paragraph::new(formatted_percent)
         .block(Block::default().borders(Borders::NONE))
@@ -194,7 +432,7 @@ fn render_mem_stats<B: Backend>(f: &mut Frame<B>, sys: &System, chunk: Rect) {
     while severity_string.len() < 10 {
         severity_string.push(' ');
     }
-    let severity_bar = color_severity(severity_string, percent);
+    let severity_bar = color_severity(severity_string, percent, theme);
     let severity_span = Spans::from(vec![
         Span::styled("[ ".to_string(), Style::default()),
         severity_bar,
@@ -214,9 +452,10 @@ fn render_mem_stats<B: Backend>(f: &mut Frame<B>, sys: &System, chunk: Rect) {
         mem_num_chunks[2],
     );
     // render available memory
+    // colored like its line in the chart below
     render_label_value(
         f,
-        "Avail Memory: ",
+        Span::styled("Avail Memory: ", Style::default().fg(theme.graph)),
         format!("{:.2} GB", (avail_mem as f64) / 1_000_000_000.0),
         mem_label_chunks[3],
         mem_num_chunks[3],
@@ -237,13 +476,39 @@ fn render_mem_stats<B: Backend>(f: &mut Frame<B>, sys: &System, chunk: Rect) {
         mem_label_chunks[5],
         mem_num_chunks[5],
     );
+
+    let chart_chunk = Layout::default()
+        .horizontal_margin(1)
+        .constraints([Constraint::Min(0)])
+        .split(mem_chunks[1]);
+    render_history_chart(
+        f,
+        history.len(),
+        &[
+            (&history.available_memory, theme.graph),
+            (&history.used_memory, theme.severity(percent)),
+        ],
+        chart_chunk[0],
+    );
 }
 
 #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
-fn render_swp_stats<B: Backend>(f: &mut Frame<B>, sys: &System, chunk: Rect) {
-    let total_swp = sys.total_swap();
-    let used_swp = sys.used_swap();
-    let free_swp = sys.free_swap();
+fn render_swp_stats<B: Backend>(
+    f: &mut Frame<B>,
+    snapshot: &Snapshot,
+    history: &History,
+    theme: &Theme,
+    chunk: Rect,
+) {
+    let total_swp = snapshot.total_swap;
+    let used_swp = snapshot.used_swap;
+    let free_swp = snapshot.free_swap;
+
+    // numbers on top, history of used swap below
+    let swp_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(SWP_ROWS), Constraint::Min(0)])
+        .split(chunk);
 
     let swp_sub_chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -252,7 +517,7 @@ fn render_swp_stats<B: Backend>(f: &mut Frame<B>, sys: &System, chunk: Rect) {
         //                            Total swp:    XX.XX GB
         //                         ...
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-        .split(chunk);
+        .split(swp_chunks[0]);
 
     let swp_label_chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -281,7 +546,7 @@ fn render_swp_stats<B: Backend>(f: &mut Frame<B>, sys: &System, chunk: Rect) {
     // render swp percentage
     let prefix = Span::styled("swp: ".to_string(), Style::default());
     let percent = ((used_swp as f64 / total_swp as f64) * 100.0) as f32;
-    let percent_color = color_severity(format!("{percent:.2}%"), percent);
+    let percent_color = color_severity(format!("{percent:.2}%"), percent, theme);
     let formatted_percent = Spans::from(vec![prefix, percent_color]);
     let percent_paragraph = Paragraph::new(formatted_percent)
         .block(Block::default().borders(Borders::NONE))
@@ -294,7 +559,7 @@ fn render_swp_stats<B: Backend>(f: &mut Frame<B>, sys: &System, chunk: Rect) {
     while severity_string.len() < 10 {
         severity_string.push(' ');
     }
-    let severity_bar = color_severity(severity_string, percent);
+    let severity_bar = color_severity(severity_string, percent, theme);
     let severity_span = Spans::from(vec![
         Span::styled("[ ".to_string(), Style::default()),
         severity_bar,
@@ -329,120 +594,76 @@ fn render_swp_stats<B: Backend>(f: &mut Frame<B>, sys: &System, chunk: Rect) {
         swp_label_chunks[4],
         swp_num_chunks[4],
     );
-}
 
-#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
-fn render_disk_stats<B: Backend>(f: &mut Frame<B>, disks: &Disks, chunk: Rect) {
-    let num_disks = disks.len();
+    let chart_chunk = Layout::default()
+        .horizontal_margin(1)
+        .constraints([Constraint::Min(0)])
+        .split(swp_chunks[1]);
+    let swap_percent = history.used_swap.back().copied().unwrap_or_default();
+    render_history_chart(
+        f,
+        history.len(),
+        &[(&history.used_swap, theme.severity(swap_percent))],
+        chart_chunk[0],
+    );
+}
 
-    let constraints =
-        vec![Constraint::Percentage(100 / (u16::try_from(num_disks).unwrap())); num_disks];
+// rows the disk i/o table needs for `count` devices: the border, the header
+// and a line per device
+fn disk_io_rows(count: u16) -> u16 {
+    3u16.saturating_add(count)
+}
 
-    let disk_sub_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(1)
-        .constraints(constraints)
+// read/write rates, iops and utilization of each block device
+fn render_disk_io<B: Backend>(
+    f: &mut Frame<B>,
+    devices: &[DiskIoSample],
+    theme: &Theme,
+    chunk: Rect,
+) {
+    let padding_chunk = Layout::default()
+        .direction(Direction::Horizontal)
+        .horizontal_margin(1)
+        .constraints([Constraint::Min(1)].as_ref())
         .split(chunk);
-
-    for (i, disk) in disks.list().iter().enumerate() {
-        let total_space = disk.total_space();
-        let available_space = disk.available_space();
-        let used_space = total_space - available_space;
-        let percentage_used = ((used_space as f64 / total_space as f64) * 100.0) as f32;
-        let name = disk.name();
-        let kind = disk.kind();
-        let fs = disk.file_system();
-        let mount_point = disk.mount_point();
-
-        let title = format!("Disk {i}");
-        let outer_block = Block::default().title(title).borders(Borders::ALL);
-        f.render_widget(outer_block, disk_sub_chunks[i]);
-
-        let disk_chunk = Layout::default()
-            .direction(Direction::Horizontal)
-            .horizontal_margin(1)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(disk_sub_chunks[i]);
-
-        let disk_label_chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .margin(1)
-            .constraints([
-                Constraint::Length(1),
-                Constraint::Length(1),
-                Constraint::Length(1),
-                Constraint::Length(1),
-                Constraint::Length(1),
-            ])
-            .split(disk_chunk[0]);
-
-        let disk_value_chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .margin(1)
-            .constraints([
-                Constraint::Length(1),
-                Constraint::Length(1),
-                Constraint::Length(1),
-                Constraint::Length(1),
-                Constraint::Length(1),
-            ])
-            .split(disk_chunk[1]);
-
-        // render mount point
-        render_label_value(
-            f,
-            "Mount Point:",
-            mount_point.to_str().unwrap().to_string(),
-            disk_label_chunks[0],
-            disk_value_chunks[0],
-        );
-
-        // render disk name
-        render_label_value(
-            f,
-            "Name: ",
-            name.to_str().unwrap().to_string(),
-            disk_label_chunks[1],
-            disk_value_chunks[1],
-        );
-
-        // render disk usage
-        let percent_label_paragraph = Paragraph::new("Usage: ")
-            .block(Block::default().borders(Borders::NONE))
-            .alignment(Alignment::Left);
-        f.render_widget(percent_label_paragraph, disk_label_chunks[2]);
-        let percent_color = color_severity(format!("{percentage_used:.2}%"), percentage_used);
-        let percent_paragraph = Paragraph::new(percent_color)
-            .block(Block::default().borders(Borders::NONE))
-            .alignment(Alignment::Right);
-        f.render_widget(percent_paragraph, disk_value_chunks[2]);
-
-        // render disk fs
-        render_label_value(
-            f,
-            "Filesystem: ",
-            fs.to_str().unwrap().to_string(),
-            disk_label_chunks[3],
-            disk_value_chunks[3],
-        );
-
-        // render disk kind
-        render_label_value(
-            f,
-            "Kind: ",
-            kind.to_string(),
-            disk_label_chunks[4],
-            disk_value_chunks[4],
+    let block = Block::default().title("Disk I/O").borders(Borders::ALL);
+    if devices.is_empty() {
+        f.render_widget(
+            Paragraph::new(" No block devices").block(block),
+            padding_chunk[0],
         );
+        return;
     }
+
+    let header_style = Style::default()
+        .fg(theme.header)
+        .add_modifier(Modifier::BOLD);
+    let header = Row::new(["Device", "Read/s", "Write/s", "IOPS", "Util"]).style(header_style);
+    let rows = devices.iter().map(|device| {
+        Row::new([
+            Cell::from(device.name.clone()),
+            Cell::from(format_bytes(device.read_rate)),
+            Cell::from(format_bytes(device.write_rate)),
+            Cell::from(device.iops.to_string()),
+            Cell::from(color_severity(
+                format!("{:.0}%", device.utilization),
+                device.utilization,
+                theme,
+            )),
+        ])
+    });
+    let table = Table::new(rows).header(header).block(block).widths(&[
+        Constraint::Percentage(18),
+        Constraint::Percentage(26),
+        Constraint::Percentage(26),
+        Constraint::Percentage(14),
+        Constraint::Percentage(14),
+    ]);
+    f.render_widget(table, padding_chunk[0]);
 }
 
-fn render_system_stats<B: Backend>(f: &mut Frame<B>, chunk: Rect) {
-    let host_name = System::host_name();
-    let version = System::os_version();
-    let uptime = System::uptime();
-    let arch = System::cpu_arch();
-    let os = System::name();
+fn render_system_stats<B: Backend>(f: &mut Frame<B>, system: &SystemInfo, chunk: Rect) {
+    let unknown = || "-".to_string();
 
     let padding_chunk = Layout::default()
         .direction(Direction::Horizontal)
@@ -487,7 +708,7 @@ fn render_system_stats<B: Backend>(f: &mut Frame<B>, chunk: Rect) {
     render_label_value(
         f,
         "Hostname: ",
-        host_name.unwrap(),
+        system.host_name.clone().unwrap_or_else(unknown),
         system_label_chunks[0],
         system_value_chunks[0],
     );
@@ -496,7 +717,7 @@ fn render_system_stats<B: Backend>(f: &mut Frame<B>, chunk: Rect) {
     render_label_value(
         f,
         "Version: ",
-        version.unwrap(),
+        system.os_version.clone().unwrap_or_else(unknown),
         system_label_chunks[1],
         system_value_chunks[1],
     );
@@ -505,7 +726,7 @@ fn render_system_stats<B: Backend>(f: &mut Frame<B>, chunk: Rect) {
     render_label_value(
         f,
         "Up-time: ",
-        uptime.to_string(),
+        system.uptime.to_string(),
         system_label_chunks[2],
         system_value_chunks[2],
     );
@@ -514,7 +735,7 @@ fn render_system_stats<B: Backend>(f: &mut Frame<B>, chunk: Rect) {
     render_label_value(
         f,
         "CPU Arch: ",
-        arch,
+        system.arch.clone(),
         system_label_chunks[3],
         system_value_chunks[3],
     );
@@ -523,7 +744,7 @@ fn render_system_stats<B: Backend>(f: &mut Frame<B>, chunk: Rect) {
     render_label_value(
         f,
         "OS: ",
-        os.unwrap(),
+        system.os_name.clone().unwrap_or_else(unknown),
         system_label_chunks[4],
         system_value_chunks[4],
     );
@@ -531,53 +752,220 @@ fn render_system_stats<B: Backend>(f: &mut Frame<B>, chunk: Rect) {
 
 pub fn create_stats_chunk<B: Backend>(
     f: &mut Frame<B>,
-    sys: &System,
-    disks: &Disks,
+    snapshot: &Snapshot,
+    app: &mut App,
+    title: String,
     chunk: Rect,
 ) -> Vec<Rect> {
+    let (history, theme) = (&app.history, &app.theme);
     // draw outer block for stats
-    let outer_block = Block::default().title("Stats").borders(Borders::ALL);
+    let outer_block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border));
     f.render_widget(outer_block, chunk);
 
-    let cpus = get_individual_cpus(sys);
-    let num_cpus = u16::try_from(sys.cpus().len()).unwrap();
+    let height = chunk.height.saturating_sub(2);
+    let networks: Vec<&NetworkSample> = snapshot
+        .networks
+        .iter()
+        .filter(|network| !(app.hide_virtual_networks && network.is_virtual))
+        .collect();
+    let disks = app.disks.entries(Instant::now());
+
+    // the cores may take up to a quarter of the panel, or everything below
+    // the chart when the cpu is shown alone. the borders and margins around
+    // them take six columns
+    let cpu_alone = Panel::ALL
+        .into_iter()
+        .filter(|panel| !matches!(panel, Panel::Cpu | Panel::Processes))
+        .all(|panel| !app.shows(panel));
+    let max_core_rows = if cpu_alone {
+        height.saturating_sub(3 + CPU_CHART_HEIGHT)
+    } else {
+        height / 4
+    };
+    let cores = core_layout(
+        snapshot.cpus.len(),
+        chunk.width.saturating_sub(6),
+        max_core_rows.max(1),
+    );
+
+    // global line, chart, the cores and the margins
+    let cpu_rows = 3 + CPU_CHART_HEIGHT + cores.1;
+    let count = |len: usize| u16::try_from(len).unwrap_or(u16::MAX).max(1);
+    let disk_count = u16::try_from(disks.len()).unwrap_or(u16::MAX);
+    let wanted = |panel, least: u16, most: u16| {
+        if app.shows(panel) {
+            (least, most)
+        } else {
+            (0, 0)
+        }
+    };
+    // from the most to the least important, the network, disk i/o and disks
+    // show as many entries as fit
+    let [cpu_height, mem_height, swp_height, network_height, disk_io_height, disk_height, system_height] =
+        panel_heights(
+            height,
+            [
+                wanted(Panel::Cpu, cpu_rows, cpu_rows),
+                wanted(
+                    Panel::Memory,
+                    MEM_ROWS + MEM_CHART_HEIGHT,
+                    MEM_ROWS + MEM_CHART_HEIGHT,
+                ),
+                wanted(
+                    Panel::Swap,
+                    SWP_ROWS + SWP_CHART_HEIGHT,
+                    SWP_ROWS + SWP_CHART_HEIGHT,
+                ),
+                wanted(
+                    Panel::Network,
+                    network::panel_rows(1),
+                    network::panel_rows(count(networks.len())),
+                ),
+                wanted(
+                    Panel::DiskIo,
+                    disk_io_rows(1),
+                    disk_io_rows(count(snapshot.disk_io.len())),
+                ),
+                wanted(
+                    Panel::Disks,
+                    disks::panel_rows(app.disk_view, disk_count.min(1)),
+                    disks::panel_rows(app.disk_view, disk_count),
+                ),
+                wanted(Panel::System, SYSTEM_ROWS, SYSTEM_ROWS),
+            ],
+        );
 
-    // splits the stats chunk into four chunks
+    // splits the stats chunk into seven chunks, the panels that are hidden or
+    // do not fit get no rows
     // 1. CPU
     // 2. Memory
     // 3. swp
-    // 4. disk usage
-    // 5. system metadata
+    // 4. network
+    // 5. disk i/o
+    // 6. disk usage
+    // 7. system metadata
     let sub_chunks = Layout::default()
         .direction(Direction::Vertical)
         .horizontal_margin(1)
         .vertical_margin(1)
         .constraints(
             [
-                Constraint::Percentage(7 + (num_cpus * 2)), // cpu
-                Constraint::Percentage(16),                 // mem
-                Constraint::Percentage(14),                 // swp
-                Constraint::Percentage(31),                 // TBD
-                Constraint::Percentage(20),                 // metadata
+                Constraint::Length(cpu_height),     // cpu
+                Constraint::Length(mem_height),     // mem
+                Constraint::Length(swp_height),     // swp
+                Constraint::Length(network_height), // network
+                Constraint::Length(disk_io_height), // disk i/o
+                Constraint::Length(disk_height),    // disks
+                Constraint::Length(system_height),  // metadata
+                Constraint::Min(0),
             ]
             .as_ref(),
         )
         .split(chunk);
 
     // render cpu stats
-    render_cpu_stats(f, sys, &cpus, sub_chunks[0]);
+    if cpu_height > 0 {
+        render_cpu_stats(f, snapshot, history, cores, theme, sub_chunks[0]);
+    }
 
     // render mem stats
-    render_mem_stats(f, sys, sub_chunks[1]);
+    if mem_height > 0 {
+        render_mem_stats(f, snapshot, history, theme, sub_chunks[1]);
+    }
 
     // render swp stats
-    render_swp_stats(f, sys, sub_chunks[2]);
+    if swp_height > 0 {
+        render_swp_stats(f, snapshot, history, theme, sub_chunks[2]);
+    }
+
+    // render network stats
+    if network_height > 0 {
+        network::render_network_stats(f, &networks, &history.networks, theme, sub_chunks[3]);
+    }
+
+    // render disk i/o stats
+    if disk_io_height > 0 {
+        render_disk_io(f, &snapshot.disk_io, theme, sub_chunks[4]);
+    }
 
-    // render disk stats
-    render_disk_stats(f, disks, sub_chunks[3]);
+    // render disk stats, scrolling stops once the last disk is in view
+    if disk_height > 0 {
+        app.disk_scroll = disks::render_disks(
+            f,
+            &disks,
+            app.disk_view,
+            app.disk_scroll,
+            theme,
+            sub_chunks[5],
+        );
+    }
 
     // render sys metadata stats
-    render_system_stats(f, sub_chunks[4]);
+    if system_height > 0 {
+        render_system_stats(f, &snapshot.system, sub_chunks[6]);
+    }
 
     sub_chunks
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn core_layout_by_space() {
+        // a line per core while they fit
+        assert_eq!(core_layout(8, 34, 10), (CoreLayout::Rows, 8));
+        // 34 columns hold two grid cells
+        assert_eq!(
+            core_layout(16, 34, 10),
+            (CoreLayout::Grid { columns: 2 }, 8)
+        );
+        assert_eq!(
+            core_layout(128, 34, 10),
+            (CoreLayout::Heatmap { columns: 34 }, 4)
+        );
+        // cut off when even the heatmap does not fit
+        assert_eq!(
+            core_layout(1024, 34, 10),
+            (CoreLayout::Heatmap { columns: 34 }, 10)
+        );
+    }
+
+    #[test]
+    fn panels_dropped_by_priority() {
+        let all = [(10, 10), (11, 11), (11, 20), (9, 9)];
+        // everything fits, the third panel grows to what it wants
+        assert_eq!(panel_heights(100, all), [10, 11, 20, 9]);
+        // no room for the last panel, the third takes its rows
+        assert_eq!(panel_heights(35, all), [10, 11, 14, 0]);
+        let hidden = [(10, 10), (11, 11), (0, 0), (9, 9)];
+        assert_eq!(panel_heights(35, hidden), [10, 11, 0, 9]);
+        assert_eq!(panel_heights(25, all), [10, 11, 0, 0]);
+        // the first panel is cut off
+        assert_eq!(panel_heights(5, all), [5, 0, 0, 0]);
+    }
+
+    #[test]
+    fn spare_rows_go_to_the_most_important_panel() {
+        let wanted = [(10, 10), (11, 30), (11, 30)];
+        assert_eq!(panel_heights(40, wanted), [10, 19, 11]);
+    }
+
+    #[test]
+    fn single_panel_takes_all_rows() {
+        let memory = [(0, 0), (11, 11), (0, 0)];
+        assert_eq!(panel_heights(40, memory), [0, 40, 0]);
+    }
+
+    #[test]
+    fn heat_symbols() {
+        assert_eq!(heat_symbol(0.0), '·');
+        assert_eq!(heat_symbol(50.0), '▒');
+        assert_eq!(heat_symbol(100.0), '█');
+        assert_eq!(heat_symbol(150.0), '█');
+    }
+}
@@ -0,0 +1,88 @@
+use crossterm::{
+    cursor::Show,
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+#[cfg(unix)]
+use signal_hook::{
+    consts::{SIGHUP, SIGINT, SIGTERM},
+    iterator::Signals,
+};
+#[cfg(unix)]
+use std::thread;
+use std::{
+    io::{self, Result, Stdout},
+    panic,
+};
+use tui::{backend::CrosstermBackend, Terminal};
+
+// signals that make the resource manager exit cleanly
+#[cfg(unix)]
+const EXIT_SIGNALS: [i32; 3] = [SIGTERM, SIGINT, SIGHUP];
+
+// puts the terminal into raw mode on the alternate screen and restores it when
+// dropped, including when main returns early with an error
+pub struct TerminalGuard {
+    pub terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl TerminalGuard {
+    pub fn new() -> Result<Self> {
+        enable_raw_mode()?;
+        let setup = || {
+            let mut stdout = io::stdout();
+            execute!(stdout, EnterAlternateScreen)?;
+            Terminal::new(CrosstermBackend::new(stdout))
+        };
+        match setup() {
+            Ok(terminal) => Ok(TerminalGuard { terminal }),
+            Err(err) => {
+                let _ = restore();
+                Err(err)
+            }
+        }
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        // nothing useful can be done if restoring fails while dropping
+        let _ = restore();
+    }
+}
+
+fn restore() -> Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, Show)
+}
+
+// restores the terminal before the panic message is printed, otherwise the
+// message is lost on the alternate screen and the shell is left in raw mode
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore();
+        default_hook(info);
+    }));
+}
+
+// calls `on_exit` from a separate thread once SIGTERM, SIGINT or SIGHUP is
+// received, instead of being killed the main loop can then restore the
+// terminal before exiting
+#[cfg(unix)]
+pub fn on_exit_signal(on_exit: impl FnOnce() + Send + 'static) -> Result<()> {
+    let mut signals = Signals::new(EXIT_SIGNALS)?;
+    thread::spawn(move || {
+        if signals.forever().next().is_some() {
+            on_exit();
+        }
+    });
+    Ok(())
+}
+
+// there are no such signals to listen for outside unix, ctrl+c still reaches the
+// app as a key press in raw mode
+#[cfg(not(unix))]
+pub fn on_exit_signal(_on_exit: impl FnOnce() + Send + 'static) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use tui::style::Color;
+
+// colors used across the ui, plus the percentages where usage turns from ok
+// to warning to critical
+#[derive(Clone)]
+pub struct Theme {
+    pub ok: Color,
+    pub warning: Color,
+    pub critical: Color,
+    pub warning_threshold: f32,
+    pub critical_threshold: f32,
+    pub border: Color,
+    pub header: Color,
+    pub highlight: Color,
+    // second line of history charts, e.g. available next to used memory
+    pub graph: Color,
+}
+
+impl Theme {
+    // "default", "light" or "mono"
+    pub fn preset(name: &str) -> Result<Self, String> {
+        let dark = Theme::default();
+        match name {
+            "default" | "dark" => Ok(dark),
+            // the light variants are hard to read on a white background
+            "light" => Ok(Theme {
+                ok: Color::Green,
+                warning: Color::Yellow,
+                critical: Color::Red,
+                border: Color::Black,
+                header: Color::Blue,
+                highlight: Color::Gray,
+                graph: Color::Blue,
+                ..dark
+            }),
+            "mono" => Ok(Theme {
+                ok: Color::Reset,
+                warning: Color::Reset,
+                critical: Color::Reset,
+                border: Color::Reset,
+                header: Color::Reset,
+                highlight: Color::DarkGray,
+                graph: Color::Reset,
+                ..dark
+            }),
+            _ => Err(format!(
+                "unknown theme {name:?}, expected default, light or mono"
+            )),
+        }
+    }
+
+    // color for a usage percentage
+    pub fn severity(&self, percent: f32) -> Color {
+        if percent > self.critical_threshold {
+            self.critical
+        } else if percent > self.warning_threshold {
+            self.warning
+        } else {
+            self.ok
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            ok: Color::LightGreen,
+            warning: Color::LightYellow,
+            critical: Color::LightRed,
+            warning_threshold: 50.0,
+            critical_threshold: 75.5,
+            border: Color::White,
+            header: Color::Yellow,
+            highlight: Color::DarkGray,
+            graph: Color::Cyan,
+        }
+    }
+}
+
+// "light_red", "LightRed", "#ff8800" or a 256 color index like "208"
+pub fn parse_color(name: &str) -> Result<Color, String> {
+    if let Some(hex) = name.strip_prefix('#') {
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+        };
+        if let (6, Some(r), Some(g), Some(b)) = (hex.len(), channel(0), channel(2), channel(4)) {
+            return Ok(Color::Rgb(r, g, b));
+        }
+        return Err(format!("invalid hex color {name:?}"));
+    }
+    if let Ok(index) = name.parse::<u8>() {
+        return Ok(Color::Indexed(index));
+    }
+
+    let color = match name.to_lowercase().replace(['_', '-', ' '], "").as_str() {
+        "reset" | "default" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return Err(format!("unknown color {name:?}")),
+    };
+    Ok(color)
+}
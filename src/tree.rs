@@ -0,0 +1,269 @@
+use crate::processes::{sort_processes, ProcessInfo, ProcessRow, SortColumn};
+use crate::users::UserCache;
+use std::collections::{HashMap, HashSet};
+use sysinfo::Pid;
+
+// options used to decide which parts of the tree are shown
+pub struct TreeOptions<'a> {
+    pub min_memory: u64,
+    // processes the user filtered for, their ancestors are shown as well
+    pub matches: &'a dyn Fn(&ProcessInfo) -> bool,
+    // only keep this many processes, picked like in the flat table, plus
+    // their ancestors
+    pub top_n: Option<usize>,
+    pub sort_column: SortColumn,
+    pub sort_descending: bool,
+    pub collapsed: &'a HashSet<Pid>,
+    pub users: &'a UserCache,
+}
+
+struct Tree<'a> {
+    children: HashMap<Pid, Vec<&'a ProcessInfo>>,
+    parents: HashMap<Pid, Pid>,
+    // memory and cpu usage of each process plus all of its descendants
+    totals: HashMap<Pid, (u64, f32)>,
+    visible: HashSet<Pid>,
+}
+
+impl<'a> Tree<'a> {
+    fn new(processes: &'a [ProcessInfo]) -> (Tree<'a>, Vec<&'a ProcessInfo>) {
+        // threads share their process' memory, counting them would inflate the totals
+        let nodes: HashMap<Pid, &ProcessInfo> = processes
+            .iter()
+            .filter(|process| !process.is_thread)
+            .map(|process| (process.pid, process))
+            .collect();
+
+        let mut roots = Vec::new();
+        let mut children: HashMap<Pid, Vec<&ProcessInfo>> = HashMap::new();
+        let mut parents = HashMap::new();
+        for process in nodes.values() {
+            let parent = process
+                .parent
+                .filter(|parent| *parent != process.pid && nodes.contains_key(parent));
+            match parent {
+                Some(parent) => {
+                    children.entry(parent).or_default().push(process);
+                    parents.insert(process.pid, parent);
+                }
+                None => roots.push(*process),
+            }
+        }
+
+        let tree = Tree {
+            children,
+            parents,
+            totals: HashMap::new(),
+            visible: HashSet::new(),
+        };
+        (tree, roots)
+    }
+
+    fn children_of(&self, pid: Pid) -> &[&'a ProcessInfo] {
+        self.children.get(&pid).map_or(&[], Vec::as_slice)
+    }
+
+    // fills in `totals` for the subtree rooted at `process` and collects the
+    // processes in it that match
+    fn compute(
+        &mut self,
+        process: &'a ProcessInfo,
+        options: &TreeOptions,
+        matched: &mut Vec<&'a ProcessInfo>,
+    ) {
+        let pid = process.pid;
+        let mut memory = process.memory;
+        let mut cpu_usage = process.cpu_usage;
+
+        let children = self.children.get(&pid).cloned().unwrap_or_default();
+        for child in children {
+            self.compute(child, options, matched);
+            let (child_memory, child_cpu) = self.totals[&child.pid];
+            memory += child_memory;
+            cpu_usage += child_cpu;
+        }
+        self.totals.insert(pid, (memory, cpu_usage));
+
+        if memory >= options.min_memory && (options.matches)(process) {
+            matched.push(process);
+        }
+    }
+
+    // ancestors of a shown process stay visible so it's clear where it came from
+    fn show(&mut self, process: &ProcessInfo) {
+        let mut pid = process.pid;
+        while self.visible.insert(pid) {
+            match self.parents.get(&pid) {
+                Some(parent) => pid = *parent,
+                None => break,
+            }
+        }
+    }
+
+    // like the flat table, except that memory and cpu usage are compared by
+    // the subtree totals the rows show
+    fn sort(&self, processes: &mut [&ProcessInfo], options: &TreeOptions) {
+        processes.sort_by(|a, b| {
+            let (a_memory, a_cpu) = self.totals[&a.pid];
+            let (b_memory, b_cpu) = self.totals[&b.pid];
+            let order = match options.sort_column {
+                SortColumn::Memory => a_memory.cmp(&b_memory),
+                SortColumn::Cpu => a_cpu.total_cmp(&b_cpu),
+                column => column.compare(a, b, options.users),
+            };
+            let order = if options.sort_descending {
+                order.reverse()
+            } else {
+                order
+            };
+            order.then_with(|| a.pid.cmp(&b.pid))
+        });
+    }
+
+    fn flatten(
+        &self,
+        process: &'a ProcessInfo,
+        indent: &str,
+        last: bool,
+        depth: usize,
+        options: &TreeOptions,
+        rows: &mut Vec<ProcessRow<'a>>,
+    ) {
+        let pid = process.pid;
+        let mut children: Vec<&ProcessInfo> = self
+            .children_of(pid)
+            .iter()
+            .copied()
+            .filter(|child| self.visible.contains(&child.pid))
+            .collect();
+        self.sort(&mut children, options);
+
+        let branch = match (depth, last) {
+            (0, _) => "",
+            (_, true) => "└─",
+            (_, false) => "├─",
+        };
+        let marker = match (children.is_empty(), options.collapsed.contains(&pid)) {
+            (true, _) => " ",
+            (false, true) => "+",
+            (false, false) => "-",
+        };
+        let (memory, cpu_usage) = self.totals[&pid];
+        rows.push(ProcessRow {
+            process,
+            prefix: format!("{indent}{branch}{marker} "),
+            memory,
+            cpu_usage,
+        });
+
+        if options.collapsed.contains(&pid) {
+            return;
+        }
+        let child_indent = match (depth, last) {
+            (0, _) => String::new(),
+            (_, true) => format!("{indent}  "),
+            (_, false) => format!("{indent}│ "),
+        };
+        for (i, child) in children.iter().enumerate() {
+            let child_last = i + 1 == children.len();
+            self.flatten(child, &child_indent, child_last, depth + 1, options, rows);
+        }
+    }
+}
+
+// lays out the processes as a tree following `Process::parent`, with memory
+// and cpu usage summed over each subtree
+pub fn build_tree<'a>(processes: &'a [ProcessInfo], options: &TreeOptions) -> Vec<ProcessRow<'a>> {
+    let (mut tree, roots) = Tree::new(processes);
+    let mut matched = Vec::new();
+    for root in &roots {
+        tree.compute(root, options, &mut matched);
+    }
+    // nearly everything descends from a handful of roots, so the top N are
+    // picked among all processes rather than among the roots
+    if let Some(top_n) = options.top_n {
+        sort_processes(
+            &mut matched,
+            options.sort_column,
+            options.sort_descending,
+            options.users,
+        );
+        matched.truncate(top_n);
+    }
+    for process in matched {
+        tree.show(process);
+    }
+
+    let mut visible_roots: Vec<&ProcessInfo> = roots
+        .into_iter()
+        .filter(|root| tree.visible.contains(&root.pid))
+        .collect();
+    tree.sort(&mut visible_roots, options);
+
+    let mut rows = Vec::new();
+    for root in visible_roots {
+        tree.flatten(root, "", true, 0, options, &mut rows);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, parent: Option<u32>, memory: u64) -> ProcessInfo {
+        ProcessInfo {
+            pid: Pid::from_u32(pid),
+            parent: parent.map(Pid::from_u32),
+            name: format!("p{pid}"),
+            cmd: Vec::new(),
+            memory,
+            cpu_usage: 0.0,
+            run_time: 0,
+            user_id: None,
+            group_id: None,
+            disk_read_rate: 0,
+            disk_write_rate: 0,
+            is_thread: false,
+        }
+    }
+
+    #[test]
+    fn sorts_and_truncates_by_subtree_totals() {
+        let processes = [
+            process(1, None, 10_000_000),
+            process(2, Some(1), 8_000_000_000),
+            process(3, None, 60_000_000),
+        ];
+        let users = UserCache::from_names(HashMap::new(), HashMap::new());
+        let collapsed = HashSet::new();
+        let mut options = TreeOptions {
+            min_memory: 0,
+            matches: &|_| true,
+            top_n: None,
+            sort_column: SortColumn::Memory,
+            sort_descending: true,
+            collapsed: &collapsed,
+            users: &users,
+        };
+        let pids = |rows: Vec<ProcessRow>| -> Vec<u32> {
+            rows.iter().map(|row| row.process.pid.as_u32()).collect()
+        };
+        assert_eq!(pids(build_tree(&processes, &options)), [1, 2, 3]);
+
+        options.top_n = Some(1);
+        assert_eq!(pids(build_tree(&processes, &options)), [1, 2]);
+
+        // under a single root the top N are still N processes, not N subtrees
+        let processes = [
+            process(1, None, 1_000_000),
+            process(2, Some(1), 20_000_000),
+            process(3, Some(1), 50_000_000),
+            process(4, Some(1), 10_000_000),
+            process(5, Some(1), 40_000_000),
+            process(6, Some(1), 30_000_000),
+        ];
+        options.top_n = Some(2);
+        assert_eq!(pids(build_tree(&processes, &options)), [1, 3, 5]);
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use sysinfo::{Groups, Users};
+
+// don't reread the user/group databases more often than this, even if
+// processes keep showing up with ids we don't know
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+// caches user and group names so they aren't looked up on every refresh
+pub struct UserCache {
+    users: HashMap<u32, String>,
+    groups: HashMap<u32, String>,
+    last_refresh: Instant,
+}
+
+impl UserCache {
+    pub fn new() -> Self {
+        let mut cache = UserCache::from_names(HashMap::new(), HashMap::new());
+        cache.reload();
+        cache
+    }
+
+    // a cache with fixed names that is never reloaded before the first
+    // REFRESH_INTERVAL has passed
+    pub fn from_names(users: HashMap<u32, String>, groups: HashMap<u32, String>) -> Self {
+        UserCache {
+            users,
+            groups,
+            last_refresh: Instant::now(),
+        }
+    }
+
+    fn reload(&mut self) {
+        let users = Users::new_with_refreshed_list();
+        self.users = users
+            .list()
+            .iter()
+            .map(|user| (**user.id(), user.name().to_string()))
+            .collect();
+        let groups = Groups::new_with_refreshed_list();
+        self.groups = groups
+            .list()
+            .iter()
+            .map(|group| (**group.id(), group.name().to_string()))
+            .collect();
+        self.last_refresh = Instant::now();
+    }
+
+    // rereads the databases if any of the ids is unknown, e.g. a user was
+    // added after startup
+    pub fn refresh_for(&mut self, ids: impl IntoIterator<Item = (Option<u32>, Option<u32>)>) {
+        if self.last_refresh.elapsed() < REFRESH_INTERVAL {
+            return;
+        }
+        let unknown = ids.into_iter().any(|(uid, gid)| {
+            uid.is_some_and(|uid| !self.users.contains_key(&uid))
+                || gid.is_some_and(|gid| !self.groups.contains_key(&gid))
+        });
+        if unknown {
+            self.reload();
+        }
+    }
+
+    // the user name, or the numeric id if there is no such user
+    pub fn user_name(&self, uid: u32) -> String {
+        self.users
+            .get(&uid)
+            .cloned()
+            .unwrap_or_else(|| uid.to_string())
+    }
+
+    // the group name, or the numeric id if there is no such group
+    pub fn group_name(&self, gid: u32) -> String {
+        self.groups
+            .get(&gid)
+            .cloned()
+            .unwrap_or_else(|| gid.to_string())
+    }
+}